@@ -1,9 +1,35 @@
 #![no_std]
+// create_collection takes its inputs flat so callers can pass plain ScVals; crate-wide
+// because the client and args code #[contractimpl] generates sits outside the impl
+#![allow(clippy::too_many_arguments)]
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, log, Address, Bytes, BytesN, Env, String, Vec, Symbol, symbol_short, IntoVal, TryFromVal
+    contract, contracterror, contractimpl, contracttype, log, Address, Bytes, BytesN, Env, String, Vec, Symbol, symbol_short, token, IntoVal, TryFromVal
 };
 
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum FactoryError {
+    NotInitialized = 1,
+    RoyaltiesTooHigh = 2,
+    RoyaltiesBelowMinimum = 3,
+    MaxCollectionsReached = 4,
+    NameTaken = 5,
+    NotAllowlisted = 6,
+    BelowMinMintAmount = 7,
+    CollectionNotFound = 8,
+    ChildCallFailed = 9,
+    WasmNotApproved = 10,
+    CollectionInactive = 11,
+    CreationDisabled = 12,
+    FactoryKilled = 13,
+    InvalidMinMintAmount = 14,
+    PriceTokenRequired = 15,
+    InvalidMetadataVersion = 16,
+    NameTooLong = 17,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub struct Config {
@@ -11,6 +37,16 @@ pub struct Config {
     pub fee_bps: u32,       // basis points (200 = 2%)
     pub fee_wallet: Address,
     pub nft_wasm_hash: BytesN<32>, // reference to NFT WASM for deployments
+    pub max_collections: Option<u128>, // cap on total collections (None = unlimited)
+    pub min_royalties_bps: u32,        // royalty floor for new collections
+    pub fee_token: Option<Address>,    // token the mint fee is collected in (None = not collected)
+    pub fee_free_below: u32,           // mints of fewer NFTs than this pay no fee
+    pub ttl_extension_ledgers: u32,    // TTL bump applied to touched entries on create/mint
+    pub refund_window_secs: u64,       // how long after a mint its fee can be refunded (0 = never)
+    pub creation_enabled: bool,        // false stops new collections; mints stay live
+    pub co_owners: Vec<Address>,       // signers that approve config change proposals
+    pub required_approvals: u32,       // co-owner approvals needed (0 = owner applies directly)
+    pub royalty_bonus_bps: u32,        // share of the platform fee paid to verified creators
 }
 
 #[derive(Clone)]
@@ -23,6 +59,15 @@ pub struct CollectionMetadata {
     pub uri_base: String,
     pub royalties_bps: u32,
     pub created_at: u64,
+    pub category: Option<String>,
+    pub min_mint_amount: u32,
+    pub royalties_enforced: bool,
+    pub verified: bool,
+    pub mint_price: u128,            // flat price per NFT, forwarded to the creator
+    pub price_token: Option<Address>,
+    pub wasm_hash: BytesN<32>,       // NFT WASM the collection was deployed from
+    pub transfer_lock_secs: u64,     // tokens are non-transferable this long after mint
+    pub metadata_version: u32,       // token metadata schema the collection follows
 }
 
 #[derive(Clone)]
@@ -40,9 +85,86 @@ pub struct CollectionSummary {
 #[contracttype]
 pub struct MintRecord {
     pub user: Address,
+    pub payer: Address,
     pub amount: u32,
     pub timestamp: u64,
-    pub fee_paid: u128,
+    pub fee_paid: u128,                // total charged to the payer, 0 when nothing was collected
+    pub creator_bonus: u128,           // part of fee_paid that went to a verified creator
+    pub fee_token: Option<Address>,    // token the fee was collected in
+    pub refunded: bool,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct CollectionSpec {
+    pub name: String,
+    pub symbol: String,
+    pub uri_base: String,
+    pub royalties_bps: u32,
+    pub salt: Option<BytesN<32>>,
+    pub category: Option<String>,
+    pub min_mint_amount: Option<u32>, // None = 1
+    pub royalties_enforced: bool,
+    pub mint_price: u128,             // 0 = free
+    pub price_token: Option<Address>, // required when mint_price > 0
+    pub initial_minter: Option<Address>, // granted the minter role on the child at creation
+    pub transfer_lock_secs: u64,      // 0 = transferable straight after mint
+    pub metadata_version: Option<u32>, // None = 1
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct FeeBreakdown {
+    pub platform: u128,
+    pub creator: u128,
+    pub total: u128,
+    pub exempt: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ActivityKind {
+    Created,
+    Minted,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ActivityEntry {
+    pub kind: ActivityKind,
+    pub actor: Address,   // creator for Created, recipient for Minted
+    pub amount: u32,      // NFTs minted (0 for Created)
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ConfigAction {
+    SetConfig(u32, Address, BytesN<32>), // fee_bps, fee_wallet, nft_wasm_hash
+    SetCoOwners(Vec<Address>, u32),      // co_owners, required_approvals
+    AddApprovedWasm(BytesN<32>),
+    RemoveApprovedWasm(BytesN<32>),
+    SetFeeToken(Option<Address>),
+    SetFeeFreeBelow(u32),
+    SetRoyaltyBonusBps(u32),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ConfigProposal {
+    pub action: ConfigAction,
+    pub approvals: Vec<Address>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ConfigChange {
+    pub old_fee_bps: u32,
+    pub new_fee_bps: u32,
+    pub old_fee_wallet: Address,
+    pub new_fee_wallet: Address,
+    pub old_nft_wasm_hash: BytesN<32>,
+    pub new_nft_wasm_hash: BytesN<32>,
 }
 
 #[derive(Clone)]
@@ -55,6 +177,14 @@ pub enum DataKey {
     CollectionMints(u128),
     NameToCollection(String),
     ContractToCollection(Address),
+    CategoryToCollections(String),
+    ApprovedWasm(BytesN<32>),
+    CollectionAllowlist(u128),
+    DeactivatedCollection(u128),
+    DeactivatedCount,
+    PendingConfig,
+    Killed,
+    OwnerHistory(u128),
 }
 
 #[derive(Clone)]
@@ -63,9 +193,18 @@ pub enum Event {
     CollectionCreated,
     MintLogged,
     FeePaid,
+    ConfigUpdated,
+    MintRefunded,
+    FeeWalletRotated,
+    FactoryKilled,
 }
 
 pub const MINTER_ROLE: Symbol = symbol_short!("MINTER");
+pub const BASE_FEE_PER_NFT: u128 = 1_000_000; // 0.1 XLM per NFT
+pub const MAX_BATCH_SIZE: u32 = 50;
+pub const DEFAULT_TTL_EXTENSION: u32 = 518_400; // ~30 days of ledgers
+pub const MAX_OWNER_HISTORY: u32 = 20;
+pub const MAX_COLLECTION_NAME_LEN: u32 = 64; // the child NFT's limit for composing token names
 
 #[contract]
 pub struct FactoryRegistry;
@@ -80,30 +219,11 @@ impl FactoryRegistry {
         fee_wallet: Address,
         nft_wasm_hash: BytesN<32>,
     ) {
-        if env.storage().persistent().has(&DataKey::Config) {
-            panic!("Already initialized");
-        }
-
         owner.require_auth();
-
-        if fee_bps > 10000 {
-            panic!("Fee BPS cannot exceed 10000 (100%)");
-        }
-
-        let config = Config {
-            owner: owner.clone(),
-            fee_bps,
-            fee_wallet,
-            nft_wasm_hash,
-        };
-
-        env.storage().persistent().set(&DataKey::Config, &config);
-        env.storage().persistent().set(&DataKey::NextCollectionId, &1u128);
-
-        log!(&env, "Factory initialized with owner: {}", owner);
+        init_factory(&env, owner, fee_bps, fee_wallet, nft_wasm_hash);
     }
 
-    /// Update factory configuration (owner only)
+    /// Update factory configuration (owner only, proposed for approval when co-owners are set)
     pub fn set_config(
         env: Env,
         fee_bps: u32,
@@ -113,292 +233,1563 @@ impl FactoryRegistry {
         let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
         config.owner.require_auth();
 
-        if fee_bps > 10000 {
-            panic!("Fee BPS cannot exceed 10000 (100%)");
-        }
+        submit_config_action(&env, config, ConfigAction::SetConfig(fee_bps, fee_wallet, nft_wasm_hash));
+    }
 
-        let new_config = Config {
-            owner: config.owner,
-            fee_bps,
-            fee_wallet: fee_wallet.clone(),
-            nft_wasm_hash,
-        };
+    /// Set the co-owners and how many must approve a config change (owner only, proposed for approval when co-owners are set)
+    pub fn set_co_owners(env: Env, co_owners: Vec<Address>, required_approvals: u32) {
+        let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
 
-        env.storage().persistent().set(&DataKey::Config, &new_config);
-        log!(&env, "Config updated: fee_bps={}, fee_wallet={}", fee_bps, fee_wallet.clone());
+        submit_config_action(&env, config, ConfigAction::SetCoOwners(co_owners, required_approvals));
     }
 
-    /// Create a new NFT collection using OpenZeppelin NFT contract
-    pub fn create_collection(
-        env: Env,
-        caller: Address,
-        name: String,
-        symbol: String,
-        uri_base: String,
-        royalties_bps: u32,
-    ) -> u128 {
-        caller.require_auth();
-
+    /// Approve the pending config change proposal; applies it once enough co-owners approve.
+    /// `action` must match the pending proposal, so an approval never lands on a swapped-in change
+    pub fn approve_config(env: Env, approver: Address, action: ConfigAction) {
         let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
-        let collection_id: u128 = env.storage().persistent()
-            .get(&DataKey::NextCollectionId)
-            .unwrap_or(1u128);
-
-        if royalties_bps > 10000 {
-            panic!("Royalties cannot exceed 10000 (100%)");
+        if !config.co_owners.contains(&approver) {
+            panic!("Not a co-owner");
         }
+        approver.require_auth();
 
-        // Deploy new NFT contract instance using the OpenZeppelin NFT WASM
-        // Use collection_id as salt for deterministic addresses
-        let mut salt_bytes = [0u8; 32];
-        let id_bytes = collection_id.to_be_bytes();
-        // Copy the u128 bytes (16 bytes) to the end of the salt array
-        for (i, &byte) in id_bytes.iter().enumerate() {
-            if i < 16 {
-                salt_bytes[16 + i] = byte;
-            }
+        let mut proposal: ConfigProposal = env.storage().persistent()
+            .get(&DataKey::PendingConfig)
+            .expect("No pending config change");
+        if proposal.action != action {
+            panic!("Pending config change does not match");
         }
-        let salt_hash = BytesN::from_array(&env, &salt_bytes);
-
-        // The WASM hash should already be a BytesN<32>, convert it properly
-        let wasm_hash = config.nft_wasm_hash;
-
-        // Deploy and initialize the NFT contract in one step
-        // deploy_v2 will call the constructor with the provided arguments
-        let contract_id = env.deployer().with_current_contract(salt_hash).deploy_v2(
-            wasm_hash,
-            (
-                &caller,           // creator as initial owner
-                &name,
-                &symbol,
-                &uri_base,
-                &royalties_bps,
-            )
-        );
+        if proposal.approvals.contains(&approver) {
+            panic!("Already approved");
+        }
+        proposal.approvals.push_back(approver);
 
-        // The NFT contract is initialized with the caller as owner/admin
-        // They can manage minting and other permissions as needed
+        if proposal.approvals.len() < config.required_approvals {
+            env.storage().persistent().set(&DataKey::PendingConfig, &proposal);
+            return;
+        }
 
-        // Create collection record
-        let collection = CollectionMetadata {
-            contract_id: contract_id.clone(),
-            name: name.clone(),
-            symbol: symbol.clone(),
-            creator: caller.clone(),
-            uri_base: uri_base.clone(),
-            royalties_bps,
-            created_at: env.ledger().timestamp(),
-        };
+        // State may have changed while the proposal was pending (e.g. a revoked hash)
+        check_config_action(&env, &proposal.action);
+        env.storage().persistent().remove(&DataKey::PendingConfig);
+        apply_config_action(&env, config, proposal.action);
+    }
 
-        // Store collection
-        env.storage().persistent().set(&DataKey::Collection(collection_id), &collection);
+    /// The config change proposal awaiting co-owner approvals, if any
+    pub fn get_pending_config(env: Env) -> Option<ConfigProposal> {
+        env.storage().persistent().get(&DataKey::PendingConfig)
+    }
 
-        // Store lookup mappings for Registry functionality
-        env.storage().persistent().set(&DataKey::NameToCollection(name.clone()), &collection_id);
-        env.storage().persistent().set(&DataKey::ContractToCollection(contract_id.clone()), &collection_id);
+    /// Approve a WASM hash for child deployments (owner only, proposed for approval when co-owners are set)
+    pub fn add_approved_wasm(env: Env, wasm_hash: BytesN<32>) {
+        let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
 
-        // Update creator's collection list
-        let mut creator_collections: Vec<u128> = env.storage().persistent()
-            .get(&DataKey::CreatorCollections(caller.clone()))
-            .unwrap_or(Vec::new(&env));
-        creator_collections.push_back(collection_id);
-        env.storage().persistent().set(&DataKey::CreatorCollections(caller.clone()), &creator_collections);
+        submit_config_action(&env, config, ConfigAction::AddApprovedWasm(wasm_hash));
+    }
 
-        // Update next collection ID
-        env.storage().persistent().set(&DataKey::NextCollectionId, &(collection_id + 1));
+    /// Revoke approval of a WASM hash (owner only, proposed for approval when co-owners are set)
+    pub fn remove_approved_wasm(env: Env, wasm_hash: BytesN<32>) {
+        let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
 
-        // Emit event
-        env.events().publish((
-            symbol_short!("col_creat"),
-            collection_id,
-            contract_id.clone(),
-            name.clone(),
-            symbol.clone(),
-            caller.clone(),
-        ), Event::CollectionCreated);
+        submit_config_action(&env, config, ConfigAction::RemoveApprovedWasm(wasm_hash));
+    }
 
-        log!(&env, "Collection {} created with ID: {}, contract: {}",
-             symbol, collection_id, contract_id);
-        collection_id
+    pub fn is_approved_wasm(env: Env, wasm_hash: BytesN<32>) -> bool {
+        env.storage().persistent().has(&DataKey::ApprovedWasm(wasm_hash))
     }
 
-    /// Mint NFTs through the factory (with fee handling)
-    pub fn mint(
-        env: Env,
-        collection_id: u128,
-        to: Address,
-        amount: u32,
-    ) {
-        to.require_auth();
+    /// Set the maximum number of collections (owner only, None = unlimited)
+    pub fn set_max_collections(env: Env, max_collections: Option<u128>) {
+        let mut config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
+
+        config.max_collections = max_collections;
 
+        env.storage().persistent().set(&DataKey::Config, &config);
+        log!(&env, "Max collections updated: {:?}", max_collections);
+    }
+
+    /// Set the token mint fees are collected in (owner only, None = fees are not collected;
+    /// proposed for approval when co-owners are set)
+    pub fn set_fee_token(env: Env, fee_token: Option<Address>) {
         let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
-        let collection: CollectionMetadata = env.storage().persistent()
-            .get(&DataKey::Collection(collection_id))
-            .ok_or("Collection not found")
-            .unwrap();
+        config.owner.require_auth();
 
-        // Calculate and handle fees if applicable
-        let fee_amount = if config.fee_bps > 0 {
-            // Charge a base fee per NFT minted
-            let base_fee_per_nft = 1_000_000u128; // 0.1 XLM per NFT
-            let total_base_fee = base_fee_per_nft * amount as u128;
-            let fee = (total_base_fee * config.fee_bps as u128) / 10000;
-
-            if fee > 0 {
-                // For simplicity, we assume the fee is paid in the native asset
-                // In a real implementation, you'd handle the actual transfer here
-                log!(&env, "Fee of {} would be charged to {}", fee, config.fee_wallet);
-
-                // Emit fee paid event
-                env.events().publish((
-                    symbol_short!("fee_paid"),
-                    fee,
-                    config.fee_wallet.clone(),
-                ), Event::FeePaid);
-            }
-            fee
-        } else {
-            0u128
-        };
+        submit_config_action(&env, config, ConfigAction::SetFeeToken(fee_token));
+    }
 
-        // Call mint on the child NFT contract
-        // Factory has minter role, so this should succeed
-        // mint(env, caller, to, amount)
-        let first_token_id = env.invoke_contract::<u32>(
-            &collection.contract_id,
-            &symbol_short!("mint"),
-            Vec::from_array(&env, [
-                env.current_contract_address().into_val(&env),
-                to.clone().into_val(&env),
-                amount.into_val(&env),
-            ])
-        );
+    /// Replace the fee wallet, emitting the previous and new addresses (owner only)
+    pub fn rotate_fee_wallet(env: Env, new_wallet: Address) {
+        let mut config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
 
-        // Log the mint for registry
-        let mint_record = MintRecord {
-            user: to.clone(),
-            amount,
-            timestamp: env.ledger().timestamp(),
-            fee_paid: fee_amount,
-        };
+        // Co-owner setups change the wallet through set_config / approve_config
+        if config.required_approvals > 0 {
+            panic!("Fee wallet change requires co-owner approval");
+        }
 
-        let mut collection_mints: Vec<MintRecord> = env.storage().persistent()
-            .get(&DataKey::CollectionMints(collection_id))
-            .unwrap_or(Vec::new(&env));
-        collection_mints.push_back(mint_record);
-        env.storage().persistent().set(&DataKey::CollectionMints(collection_id), &collection_mints);
+        let old_wallet = config.fee_wallet.clone();
+        config.fee_wallet = new_wallet.clone();
+
+        env.storage().persistent().set(&DataKey::Config, &config);
 
-        // Emit mint logged event
         env.events().publish((
-            symbol_short!("mint_log"),
-            collection_id,
-            to.clone(),
-            amount,
-            fee_amount,
-        ), Event::MintLogged);
+            Symbol::new(&env, "fee_wallet_rotated"),
+            old_wallet,
+            new_wallet.clone(),
+        ), Event::FeeWalletRotated);
 
-        log!(&env, "Minted {} NFTs for collection {}, starting from token ID {}",
-             amount, collection_id, first_token_id);
+        log!(&env, "Fee wallet rotated to: {}", new_wallet);
     }
 
-    /// Get collection details
-    pub fn get_collection(env: Env, collection_id: u128) -> CollectionMetadata {
-        env.storage().persistent()
-            .get(&DataKey::Collection(collection_id))
-            .ok_or("Collection not found")
-            .unwrap()
+    /// Set the share of each mint fee routed to verified collections' creators
+    /// (owner only, proposed for approval when co-owners are set)
+    pub fn set_royalty_bonus_bps(env: Env, royalty_bonus_bps: u32) {
+        let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
+
+        submit_config_action(&env, config, ConfigAction::SetRoyaltyBonusBps(royalty_bonus_bps));
     }
 
-    /// List collections with pagination
-    pub fn list_collections(env: Env, cursor: Option<u128>, limit: Option<u32>) -> Vec<CollectionSummary> {
-        let next_id: u128 = env.storage().persistent()
-            .get(&DataKey::NextCollectionId)
-            .unwrap_or(1u128);
+    /// Set the mint amount below which no fee is charged (owner only, 0 = always charge;
+    /// proposed for approval when co-owners are set)
+    pub fn set_fee_free_below(env: Env, fee_free_below: u32) {
+        let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
 
-        let start = cursor.unwrap_or(1u128);
-        let limit = limit.unwrap_or(10u32);
-        let end = (start + limit as u128).min(next_id);
+        submit_config_action(&env, config, ConfigAction::SetFeeFreeBelow(fee_free_below));
+    }
 
-        let mut collections = Vec::new(&env);
+    /// Set the TTL extension applied to entries touched by create/mint (owner only, 0 = no bump)
+    pub fn set_ttl_extension(env: Env, ledgers: u32) {
+        let mut config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
 
-        for id in start..end {
-            if let Some(collection) = env.storage().persistent().get::<DataKey, CollectionMetadata>(&DataKey::Collection(id)) {
-                collections.push_back(CollectionSummary {
-                    collection_id: id,
-                    contract_id: collection.contract_id,
-                    name: collection.name,
-                    symbol: collection.symbol,
-                    creator: collection.creator,
-                    created_at: collection.created_at,
-                });
-            }
-        }
+        config.ttl_extension_ledgers = ledgers;
 
-        collections
+        env.storage().persistent().set(&DataKey::Config, &config);
+        log!(&env, "TTL extension updated: {}", ledgers);
     }
 
-    /// List collections by creator
-    pub fn list_by_creator(env: Env, creator: Address) -> Vec<u128> {
-        env.storage().persistent()
-            .get(&DataKey::CreatorCollections(creator))
-            .unwrap_or(Vec::new(&env))
-    }
+    /// Irreversibly stop all collection creation and mints (owner only); reads keep working
+    pub fn permanently_disable(env: Env) {
+        let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
 
-    /// Get mint history for a collection
-    pub fn get_collection_mints(env: Env, collection_id: u128) -> Vec<MintRecord> {
-        env.storage().persistent()
-            .get(&DataKey::CollectionMints(collection_id))
-            .unwrap_or(Vec::new(&env))
+        if is_killed(&env) {
+            panic!("{}", error_message(FactoryError::FactoryKilled));
+        }
+
+        // Persistent entries are archived rather than dropped when their TTL lapses,
+        // so the flag can't silently disappear; keep it live for as long as allowed anyway
+        let max_ttl = env.storage().max_ttl();
+        env.storage().persistent().set(&DataKey::Killed, &true);
+        env.storage().persistent().extend_ttl(&DataKey::Killed, max_ttl, max_ttl);
+
+        env.events().publish((
+            symbol_short!("killed"),
+            config.owner,
+        ), Event::FactoryKilled);
+
+        log!(&env, "Factory permanently disabled");
     }
 
-    /// Get current config
-    pub fn get_config(env: Env) -> Config {
-        env.storage().persistent().get(&DataKey::Config).unwrap()
+    /// Whether the factory has been permanently disabled
+    pub fn is_killed(env: Env) -> bool {
+        is_killed(&env)
     }
 
-    /// Get next collection ID
-    pub fn get_next_collection_id(env: Env) -> u128 {
-        env.storage().persistent()
-            .get(&DataKey::NextCollectionId)
-            .unwrap_or(1u128)
+    /// Enable or disable new collection creation (owner only)
+    pub fn set_creation_enabled(env: Env, enabled: bool) {
+        let mut config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
+
+        config.creation_enabled = enabled;
+
+        env.storage().persistent().set(&DataKey::Config, &config);
+        log!(&env, "Collection creation enabled: {}", enabled);
     }
 
-    /// Get total number of collections
-    pub fn get_total_collections(env: Env) -> u128 {
-        let next_id: u128 = env.storage().persistent()
-            .get(&DataKey::NextCollectionId)
-            .unwrap_or(1u128);
-        if next_id > 1 { next_id - 1 } else { 0 }
+    /// Set how long after a mint the owner may refund its fee (owner only, 0 = never)
+    pub fn set_refund_window(env: Env, window_secs: u64) {
+        let mut config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
+
+        config.refund_window_secs = window_secs;
+
+        env.storage().persistent().set(&DataKey::Config, &config);
+        log!(&env, "Refund window updated: {}", window_secs);
     }
 
-    /// Find collection by name
-    pub fn find_by_name(env: Env, name: String) -> Option<CollectionMetadata> {
-        if let Some(collection_id) = env.storage().persistent().get::<DataKey, u128>(&DataKey::NameToCollection(name)) {
-            env.storage().persistent().get(&DataKey::Collection(collection_id))
-        } else {
-            None
+    /// Refund a mint's fee from the fee wallet to its payer within the refund window (owner only).
+    /// Only the platform's share comes back: a verified creator's bonus has already left the
+    /// factory's control and is not refundable
+    pub fn refund_mint(env: Env, collection_id: u128, mint_index: u32) {
+        let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
+
+        let mut mints: Vec<MintRecord> = env.storage().persistent()
+            .get(&DataKey::CollectionMints(collection_id))
+            .unwrap_or(Vec::new(&env));
+        let mut record = mints.get(mint_index).expect("Mint not found");
+
+        if record.refunded {
+            panic!("Mint already refunded");
+        }
+        if config.refund_window_secs == 0 {
+            panic!("Refunds are disabled");
+        }
+        if env.ledger().timestamp() > record.timestamp.saturating_add(config.refund_window_secs) {
+            panic!("Refund window elapsed");
         }
-    }
 
-    /// Find collection by contract ID
-    pub fn find_by_contract_id(env: Env, contract_id: Address) -> Option<CollectionMetadata> {
-        if let Some(collection_id) = env.storage().persistent().get::<DataKey, u128>(&DataKey::ContractToCollection(contract_id)) {
-            env.storage().persistent().get(&DataKey::Collection(collection_id))
-        } else {
-            None
+        // Refund in the token the fee was taken in, even if the factory has since switched;
+        // mints that were not charged carry no token and move nothing
+        let refund = record.fee_paid - record.creator_bonus;
+        if let Some(fee_token) = &record.fee_token {
+            token::Client::new(&env, fee_token)
+                .transfer(&config.fee_wallet, &record.payer, &(refund as i128));
         }
+
+        record.refunded = true;
+        mints.set(mint_index, record.clone());
+        env.storage().persistent().set(&DataKey::CollectionMints(collection_id), &mints);
+
+        env.events().publish((
+            symbol_short!("mint_rfnd"),
+            collection_id,
+            record.payer,
+            refund,
+        ), Event::MintRefunded);
+
+        log!(&env, "Refunded mint {} of collection {}", mint_index, collection_id);
     }
 
-    /// Get collection metadata by contract ID (for Registry interface)
-    pub fn get_collection_by_contract(env: Env, contract_id: Address) -> CollectionMetadata {
-        let collection_id: u128 = env.storage().persistent()
-            .get(&DataKey::ContractToCollection(contract_id))
+    /// Extend the TTL of a collection's record and indexes by `ledgers`
+    pub fn extend_collection_ttl(env: Env, collection_id: u128, ledgers: u32) {
+        let collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
             .ok_or("Collection not found")
             .unwrap();
 
-        env.storage().persistent()
-            .get(&DataKey::Collection(collection_id))
-            .ok_or("Collection metadata not found")
-            .unwrap()
+        bump_collection_ttl(&env, collection_id, &collection, ledgers);
+    }
+
+    /// Set the minimum royalties new collections must carry (owner only)
+    pub fn set_min_royalties_bps(env: Env, min_royalties_bps: u32) {
+        let mut config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
+
+        if min_royalties_bps > 10000 {
+            panic!("Royalties cannot exceed 10000 (100%)");
+        }
+
+        config.min_royalties_bps = min_royalties_bps;
+
+        env.storage().persistent().set(&DataKey::Config, &config);
+        log!(&env, "Min royalties updated: {}", min_royalties_bps);
+    }
+
+    /// Initialize the factory and create its first collection in one call
+    pub fn initialize_with_collection(
+        env: Env,
+        owner: Address,
+        fee_bps: u32,
+        fee_wallet: Address,
+        nft_wasm_hash: BytesN<32>,
+        first_collection_spec: CollectionSpec,
+    ) -> u128 {
+        owner.require_auth();
+        init_factory(&env, owner.clone(), fee_bps, fee_wallet, nft_wasm_hash);
+        create_collection_from_spec(&env, owner, first_collection_spec)
     }
-}
\ No newline at end of file
+
+    /// Create a new NFT collection using OpenZeppelin NFT contract
+    pub fn create_collection(
+        env: Env,
+        caller: Address,
+        name: String,
+        symbol: String,
+        uri_base: String,
+        royalties_bps: u32,
+        salt: Option<BytesN<32>>,
+        category: Option<String>,
+        min_mint_amount: Option<u32>,
+        royalties_enforced: bool,
+        initial_minter: Option<Address>,
+    ) -> u128 {
+        caller.require_auth();
+
+        let spec = CollectionSpec {
+            name,
+            symbol,
+            uri_base,
+            royalties_bps,
+            salt,
+            category,
+            min_mint_amount,
+            royalties_enforced,
+            mint_price: 0,
+            price_token: None,
+            initial_minter,
+            transfer_lock_secs: 0,
+            metadata_version: None,
+        };
+        create_collection_from_spec(&env, caller, spec)
+    }
+
+    /// Create a new NFT collection from a full spec (mint price and token included)
+    pub fn create_collection_with_spec(env: Env, caller: Address, spec: CollectionSpec) -> u128 {
+        caller.require_auth();
+        create_collection_from_spec(&env, caller, spec)
+    }
+
+    /// Dry-run the create_collection checks on a spec without deploying or writing anything
+    pub fn validate_collection_spec(env: Env, spec: CollectionSpec) -> Result<(), FactoryError> {
+        check_collection_spec(&env, &spec)
+    }
+
+    /// Mint NFTs through the factory (with fee handling)
+    pub fn mint(
+        env: Env,
+        collection_id: u128,
+        to: Address,
+        amount: u32,
+    ) -> (u32, u32) {
+        to.require_auth();
+
+        mint_to(&env, collection_id, &to, &to, amount)
+    }
+
+    /// Mint NFTs to `to` with the fee charged to `payer` (gifting)
+    pub fn mint_for(
+        env: Env,
+        collection_id: u128,
+        payer: Address,
+        to: Address,
+        amount: u32,
+    ) -> (u32, u32) {
+        payer.require_auth();
+
+        mint_to(&env, collection_id, &payer, &to, amount)
+    }
+
+    /// Whether minting `amount` NFTs to `to` would pass the factory's gating checks
+    /// and fit within the child contract's remaining supply
+    pub fn can_mint(env: Env, collection_id: u128, to: Address, amount: u32) -> bool {
+        let collection = match env.storage().persistent().get::<DataKey, CollectionMetadata>(&DataKey::Collection(collection_id)) {
+            Some(collection) => collection,
+            None => return false,
+        };
+        if check_mint_eligibility(&env, collection_id, &collection, &to, amount).is_err() {
+            return false;
+        }
+
+        // The supply cap lives on the child; a child that can't answer can't mint either
+        match env.try_invoke_contract::<Option<u32>, soroban_sdk::Error>(
+            &collection.contract_id,
+            &Symbol::new(&env, "remaining_supply"),
+            Vec::new(&env),
+        ) {
+            Ok(Ok(Some(remaining))) => amount <= remaining,
+            Ok(Ok(None)) => true,
+            _ => false,
+        }
+    }
+
+    /// Grant `minter` the minter role on several collections (each creator must authorize)
+    pub fn grant_minter_to_collections(env: Env, collection_ids: Vec<u128>, minter: Address) {
+        // Creators sign once even when they own several of the listed collections
+        let mut authorized: Vec<Address> = Vec::new(&env);
+
+        for collection_id in collection_ids.iter() {
+            let collection: CollectionMetadata = env.storage().persistent()
+                .get(&DataKey::Collection(collection_id))
+                .ok_or("Collection not found")
+                .unwrap();
+
+            if !authorized.contains(&collection.creator) {
+                collection.creator.require_auth();
+                authorized.push_back(collection.creator.clone());
+            }
+
+            // The creator is the child's admin, so it authorizes set_minter there too
+            env.invoke_contract::<()>(
+                &collection.contract_id,
+                &Symbol::new(&env, "set_minter"),
+                Vec::from_array(&env, [
+                    collection.creator.into_val(&env),
+                    minter.clone().into_val(&env),
+                ])
+            );
+        }
+
+        log!(&env, "Minter {} granted on {} collections", minter, collection_ids.len());
+    }
+
+    /// Set the mint allowlist for a collection (creator only, empty list clears it)
+    pub fn set_collection_allowlist(env: Env, collection_id: u128, addresses: Vec<Address>) {
+        let collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .ok_or("Collection not found")
+            .unwrap();
+        collection.creator.require_auth();
+
+        if addresses.is_empty() {
+            env.storage().persistent().remove(&DataKey::CollectionAllowlist(collection_id));
+        } else {
+            env.storage().persistent().set(&DataKey::CollectionAllowlist(collection_id), &addresses);
+        }
+
+        log!(&env, "Allowlist for collection {} set with {} addresses", collection_id, addresses.len());
+    }
+
+    /// Deactivate or reactivate a collection; deactivated collections cannot mint (creator only)
+    pub fn set_collection_active(env: Env, collection_id: u128, active: bool) {
+        let collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .ok_or("Collection not found")
+            .unwrap();
+        collection.creator.require_auth();
+
+        // Nothing to do when the collection is already in the requested state
+        let key = DataKey::DeactivatedCollection(collection_id);
+        if active != env.storage().persistent().has(&key) {
+            return;
+        }
+
+        let deactivated: u128 = env.storage().persistent()
+            .get(&DataKey::DeactivatedCount)
+            .unwrap_or(0u128);
+        if active {
+            env.storage().persistent().remove(&key);
+            env.storage().persistent().set(&DataKey::DeactivatedCount, &(deactivated - 1));
+        } else {
+            env.storage().persistent().set(&key, &true);
+            env.storage().persistent().set(&DataKey::DeactivatedCount, &(deactivated + 1));
+        }
+
+        log!(&env, "Collection {} active: {}", collection_id, active);
+    }
+
+    /// Hand a collection, its child contract's admin role and its payout address to a new creator
+    /// (current and new creator must both authorize)
+    pub fn transfer_collection(env: Env, collection_id: u128, new_creator: Address) {
+        let mut collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .ok_or("Collection not found")
+            .unwrap();
+        collection.creator.require_auth();
+
+        if new_creator == collection.creator {
+            panic!("Already the collection owner");
+        }
+        new_creator.require_auth();
+
+        // The child's admin transfer is two-step: the current admin offers it and the
+        // new creator accepts it in the same call, so the offer only needs to outlive it
+        env.invoke_contract::<()>(
+            &collection.contract_id,
+            &Symbol::new(&env, "transfer_admin_role"),
+            Vec::from_array(&env, [
+                new_creator.clone().into_val(&env),
+                (env.ledger().sequence() + 1).into_val(&env),
+            ])
+        );
+        env.invoke_contract::<()>(
+            &collection.contract_id,
+            &Symbol::new(&env, "accept_admin_transfer"),
+            Vec::new(&env)
+        );
+
+        // Sale proceeds and default royalties follow the collection to its new owner
+        env.invoke_contract::<()>(
+            &collection.contract_id,
+            &Symbol::new(&env, "set_payout_address"),
+            Vec::from_array(&env, [new_creator.clone().into_val(&env)])
+        );
+
+        let mut history = Self::get_collection_owner_history(env.clone(), collection_id);
+        if history.len() >= MAX_OWNER_HISTORY {
+            history.pop_front();
+        }
+        history.push_back((new_creator.clone(), env.ledger().timestamp()));
+        env.storage().persistent().set(&DataKey::OwnerHistory(collection_id), &history);
+
+        // Move the collection between the creators' indexes
+        let old_key = DataKey::CreatorCollections(collection.creator.clone());
+        let mut old_collections: Vec<u128> = env.storage().persistent()
+            .get(&old_key)
+            .unwrap_or(Vec::new(&env));
+        if let Some(index) = old_collections.first_index_of(collection_id) {
+            old_collections.remove(index);
+        }
+        env.storage().persistent().set(&old_key, &old_collections);
+
+        let new_key = DataKey::CreatorCollections(new_creator.clone());
+        let mut new_collections: Vec<u128> = env.storage().persistent()
+            .get(&new_key)
+            .unwrap_or(Vec::new(&env));
+        new_collections.push_back(collection_id);
+        env.storage().persistent().set(&new_key, &new_collections);
+
+        collection.creator = new_creator.clone();
+        env.storage().persistent().set(&DataKey::Collection(collection_id), &collection);
+
+        log!(&env, "Collection {} transferred to {}", collection_id, new_creator);
+    }
+
+    /// Owners of a collection with the time each took control, oldest first (bounded)
+    pub fn get_collection_owner_history(env: Env, collection_id: u128) -> Vec<(Address, u64)> {
+        if let Some(history) = env.storage().persistent().get(&DataKey::OwnerHistory(collection_id)) {
+            return history;
+        }
+
+        // Untransferred collections have only their creator
+        let collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .ok_or("Collection not found")
+            .unwrap();
+        Vec::from_array(&env, [(collection.creator, collection.created_at)])
+    }
+
+    /// Update the metadata schema version a collection follows (creator only)
+    pub fn set_metadata_version(env: Env, collection_id: u128, metadata_version: u32) {
+        let mut collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .ok_or("Collection not found")
+            .unwrap();
+        collection.creator.require_auth();
+
+        if metadata_version == 0 {
+            panic!("Metadata version must be at least 1");
+        }
+
+        collection.metadata_version = metadata_version;
+        env.storage().persistent().set(&DataKey::Collection(collection_id), &collection);
+
+        log!(&env, "Collection {} metadata version: {}", collection_id, metadata_version);
+    }
+
+    /// Check whether a collection is active
+    pub fn is_collection_active(env: Env, collection_id: u128) -> bool {
+        !env.storage().persistent().has(&DataKey::DeactivatedCollection(collection_id))
+    }
+
+    /// Mark a collection as verified by the platform (owner only)
+    pub fn set_verified(env: Env, collection_id: u128, verified: bool) {
+        let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        config.owner.require_auth();
+
+        let mut collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .ok_or("Collection not found")
+            .unwrap();
+        collection.verified = verified;
+        env.storage().persistent().set(&DataKey::Collection(collection_id), &collection);
+
+        log!(&env, "Collection {} verified: {}", collection_id, verified);
+    }
+
+    /// Check whether an address is on a collection's allowlist
+    pub fn is_allowlisted(env: Env, collection_id: u128, addr: Address) -> bool {
+        env.storage().persistent()
+            .get::<DataKey, Vec<Address>>(&DataKey::CollectionAllowlist(collection_id))
+            .map(|allowlist| allowlist.contains(&addr))
+            .unwrap_or(false)
+    }
+
+    /// Quote the itemized fee for a mint without executing it
+    pub fn quote_mint_fee_breakdown(env: Env, collection_id: u128, _to: Address, amount: u32) -> FeeBreakdown {
+        let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+        let collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .ok_or("Collection not found")
+            .unwrap();
+
+        fee_breakdown(&config, amount, collection.verified)
+    }
+
+    /// Get collection details
+    pub fn get_collection(env: Env, collection_id: u128) -> CollectionMetadata {
+        env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .ok_or("Collection not found")
+            .unwrap()
+    }
+
+    /// Get several collections at once (None for missing ids)
+    pub fn get_collections_by_ids(env: Env, ids: Vec<u128>) -> Vec<Option<CollectionMetadata>> {
+        if ids.len() > MAX_BATCH_SIZE {
+            panic!("Too many ids requested");
+        }
+
+        let mut collections = Vec::new(&env);
+        for id in ids.iter() {
+            collections.push_back(env.storage().persistent().get(&DataKey::Collection(id)));
+        }
+
+        collections
+    }
+
+    /// List collections with pagination
+    pub fn list_collections(env: Env, cursor: Option<u128>, limit: Option<u32>) -> Vec<CollectionSummary> {
+        let next_id: u128 = env.storage().persistent()
+            .get(&DataKey::NextCollectionId)
+            .unwrap_or(1u128);
+
+        let start = cursor.unwrap_or(1u128);
+        let limit = limit.unwrap_or(10u32);
+        let end = (start + limit as u128).min(next_id);
+
+        let mut collections = Vec::new(&env);
+
+        for id in start..end {
+            if let Some(collection) = env.storage().persistent().get::<DataKey, CollectionMetadata>(&DataKey::Collection(id)) {
+                collections.push_back(CollectionSummary {
+                    collection_id: id,
+                    contract_id: collection.contract_id,
+                    name: collection.name,
+                    symbol: collection.symbol,
+                    creator: collection.creator,
+                    created_at: collection.created_at,
+                });
+            }
+        }
+
+        collections
+    }
+
+    /// Collections whose name starts with `prefix` (byte comparison), at most `limit`
+    pub fn find_by_name_prefix(env: Env, prefix: String, limit: u32) -> Vec<CollectionSummary> {
+        let next_id: u128 = env.storage().persistent()
+            .get(&DataKey::NextCollectionId)
+            .unwrap_or(1u128);
+        let prefix = Bytes::from(prefix);
+
+        let mut collections = Vec::new(&env);
+
+        for id in 1..next_id {
+            if collections.len() >= limit {
+                break;
+            }
+            if let Some(collection) = env.storage().persistent().get::<DataKey, CollectionMetadata>(&DataKey::Collection(id)) {
+                let name = Bytes::from(&collection.name);
+                if name.len() >= prefix.len() && name.slice(0..prefix.len()) == prefix {
+                    collections.push_back(CollectionSummary {
+                        collection_id: id,
+                        contract_id: collection.contract_id,
+                        name: collection.name,
+                        symbol: collection.symbol,
+                        creator: collection.creator,
+                        created_at: collection.created_at,
+                    });
+                }
+            }
+        }
+
+        collections
+    }
+
+    /// Export full metadata for the active collections among ids `start..start + limit` (for migrations)
+    pub fn export_collections(env: Env, start: u128, limit: u32) -> Vec<CollectionMetadata> {
+        let next_id: u128 = env.storage().persistent()
+            .get(&DataKey::NextCollectionId)
+            .unwrap_or(1u128);
+
+        let start = start.max(1u128);
+        let end = (start + limit as u128).min(next_id);
+
+        let mut collections = Vec::new(&env);
+
+        for id in start..end {
+            // Deactivated collections are not carried over
+            if env.storage().persistent().has(&DataKey::DeactivatedCollection(id)) {
+                continue;
+            }
+            if let Some(collection) = env.storage().persistent().get::<DataKey, CollectionMetadata>(&DataKey::Collection(id)) {
+                collections.push_back(collection);
+            }
+        }
+
+        collections
+    }
+
+    /// WASM hash a collection was deployed with
+    pub fn get_collection_wasm_hash(env: Env, collection_id: u128) -> BytesN<32> {
+        Self::get_collection(env, collection_id).wasm_hash
+    }
+
+    /// Get the current admin of a collection's child NFT contract
+    pub fn get_collection_child_admin(env: Env, collection_id: u128) -> Result<Address, FactoryError> {
+        let collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .ok_or(FactoryError::CollectionNotFound)?;
+
+        match env.try_invoke_contract::<Address, soroban_sdk::Error>(
+            &collection.contract_id,
+            &Symbol::new(&env, "contract_admin"),
+            Vec::new(&env),
+        ) {
+            Ok(Ok(admin)) => Ok(admin),
+            _ => Err(FactoryError::ChildCallFailed),
+        }
+    }
+
+    /// Whether a collection's child contract answers its metadata getter (false if it reverts)
+    pub fn is_collection_healthy(env: Env, collection_id: u128) -> bool {
+        let collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .ok_or("Collection not found")
+            .unwrap();
+
+        matches!(
+            env.try_invoke_contract::<String, soroban_sdk::Error>(
+                &collection.contract_id,
+                &Symbol::new(&env, "name"),
+                Vec::new(&env),
+            ),
+            Ok(Ok(_))
+        )
+    }
+
+    /// Remaining mintable supply of a collection's child contract (None = uncapped)
+    pub fn collection_remaining_supply(env: Env, collection_id: u128) -> Option<u32> {
+        let collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .ok_or("Collection not found")
+            .unwrap();
+
+        env.invoke_contract::<Option<u32>>(
+            &collection.contract_id,
+            &Symbol::new(&env, "remaining_supply"),
+            Vec::new(&env),
+        )
+    }
+
+    /// List collections by creator
+    pub fn list_by_creator(env: Env, creator: Address) -> Vec<u128> {
+        env.storage().persistent()
+            .get(&DataKey::CreatorCollections(creator))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// List collections in a category
+    pub fn list_by_category(env: Env, category: String) -> Vec<u128> {
+        env.storage().persistent()
+            .get(&DataKey::CategoryToCollections(category))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// List verified collections, at most `limit`
+    pub fn list_verified(env: Env, limit: u32) -> Vec<u128> {
+        let next_id: u128 = env.storage().persistent()
+            .get(&DataKey::NextCollectionId)
+            .unwrap_or(1u128);
+
+        let mut verified = Vec::new(&env);
+        for id in 1..next_id {
+            if verified.len() >= limit {
+                break;
+            }
+            if let Some(collection) = env.storage().persistent().get::<DataKey, CollectionMetadata>(&DataKey::Collection(id)) {
+                if collection.verified {
+                    verified.push_back(id);
+                }
+            }
+        }
+
+        verified
+    }
+
+    /// List verified collections in a category, at most `limit`
+    pub fn list_verified_by_category(env: Env, category: String, limit: u32) -> Vec<u128> {
+        let mut verified = Vec::new(&env);
+        for id in Self::list_by_category(env.clone(), category).iter() {
+            if verified.len() >= limit {
+                break;
+            }
+            if let Some(collection) = env.storage().persistent().get::<DataKey, CollectionMetadata>(&DataKey::Collection(id)) {
+                if collection.verified {
+                    verified.push_back(id);
+                }
+            }
+        }
+
+        verified
+    }
+
+    /// Get mint history for a collection
+    pub fn get_collection_mints(env: Env, collection_id: u128) -> Vec<MintRecord> {
+        env.storage().persistent()
+            .get(&DataKey::CollectionMints(collection_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Number of mints logged for a collection within the last `window_secs` (inclusive)
+    pub fn mint_velocity(env: Env, collection_id: u128, window_secs: u64) -> u32 {
+        let since = env.ledger().timestamp().saturating_sub(window_secs);
+
+        // Mints are appended in ledger order, so stop at the first one outside the window
+        let mut count = 0u32;
+        for mint in Self::get_collection_mints(env, collection_id).iter().rev() {
+            if mint.timestamp < since {
+                break;
+            }
+            count += 1;
+        }
+        count
+    }
+
+    /// Addresses that received the most NFTs from a collection, highest first (ties keep
+    /// first-mint order), at most `limit`
+    pub fn get_top_minters(env: Env, collection_id: u128, limit: u32) -> Vec<(Address, u32)> {
+        // Sum minted amounts per recipient
+        let mut totals: Vec<(Address, u32)> = Vec::new(&env);
+        for mint in Self::get_collection_mints(env.clone(), collection_id).iter() {
+            match totals.iter().position(|(user, _)| user == mint.user) {
+                Some(index) => {
+                    let (user, amount) = totals.get_unchecked(index as u32);
+                    totals.set(index as u32, (user, amount + mint.amount));
+                }
+                None => totals.push_back((mint.user, mint.amount)),
+            }
+        }
+
+        // Insert each total ahead of the first strictly smaller one
+        let mut ranked: Vec<(Address, u32)> = Vec::new(&env);
+        for entry in totals.iter() {
+            let index = ranked.iter()
+                .position(|(_, amount)| amount < entry.1)
+                .map(|index| index as u32)
+                .unwrap_or(ranked.len());
+            ranked.insert(index, entry);
+        }
+
+        while ranked.len() > limit {
+            ranked.pop_back();
+        }
+        ranked
+    }
+
+    /// Recent activity for a collection (creation and mints), newest first, at most `limit` entries
+    pub fn get_collection_activity(env: Env, collection_id: u128, limit: u32) -> Vec<ActivityEntry> {
+        let collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .ok_or("Collection not found")
+            .unwrap();
+        let mints = Self::get_collection_mints(env.clone(), collection_id);
+
+        // Mint records are appended in ledger order and all follow the creation
+        let mut activity = Vec::new(&env);
+        for mint in mints.iter().rev() {
+            if activity.len() >= limit {
+                return activity;
+            }
+            activity.push_back(ActivityEntry {
+                kind: ActivityKind::Minted,
+                actor: mint.user,
+                amount: mint.amount,
+                timestamp: mint.timestamp,
+            });
+        }
+        if activity.len() < limit {
+            activity.push_back(ActivityEntry {
+                kind: ActivityKind::Created,
+                actor: collection.creator,
+                amount: 0,
+                timestamp: collection.created_at,
+            });
+        }
+
+        activity
+    }
+
+    /// Get current config
+    pub fn get_config(env: Env) -> Config {
+        env.storage().persistent().get(&DataKey::Config).unwrap()
+    }
+
+    /// Get the factory owner
+    pub fn get_owner(env: Env) -> Result<Address, FactoryError> {
+        let config: Config = env.storage().persistent()
+            .get(&DataKey::Config)
+            .ok_or(FactoryError::NotInitialized)?;
+        Ok(config.owner)
+    }
+
+    /// Get next collection ID
+    pub fn get_next_collection_id(env: Env) -> u128 {
+        env.storage().persistent()
+            .get(&DataKey::NextCollectionId)
+            .unwrap_or(1u128)
+    }
+
+    /// Get total number of collections
+    pub fn get_total_collections(env: Env) -> u128 {
+        let next_id: u128 = env.storage().persistent()
+            .get(&DataKey::NextCollectionId)
+            .unwrap_or(1u128);
+        if next_id > 1 { next_id - 1 } else { 0 }
+    }
+
+    /// Get number of collections that have not been deactivated
+    pub fn get_active_collection_count(env: Env) -> u128 {
+        let deactivated: u128 = env.storage().persistent()
+            .get(&DataKey::DeactivatedCount)
+            .unwrap_or(0u128);
+        Self::get_total_collections(env) - deactivated
+    }
+
+    /// The `index`-th active collection (zero-based, in id order), skipping deactivated ones
+    pub fn collection_at(env: Env, index: u128) -> Option<CollectionMetadata> {
+        let next_id: u128 = env.storage().persistent()
+            .get(&DataKey::NextCollectionId)
+            .unwrap_or(1u128);
+
+        let mut position = 0u128;
+        for id in 1..next_id {
+            if env.storage().persistent().has(&DataKey::DeactivatedCollection(id)) {
+                continue;
+            }
+            if let Some(collection) = env.storage().persistent().get::<DataKey, CollectionMetadata>(&DataKey::Collection(id)) {
+                if position == index {
+                    return Some(collection);
+                }
+                position += 1;
+            }
+        }
+
+        None
+    }
+
+    /// Count collections created within [from_ts, to_ts]
+    pub fn count_collections_by_time(env: Env, from_ts: u64, to_ts: u64) -> u128 {
+        let next_id: u128 = env.storage().persistent()
+            .get(&DataKey::NextCollectionId)
+            .unwrap_or(1u128);
+
+        let mut count = 0u128;
+        for id in 1..next_id {
+            if let Some(collection) = env.storage().persistent().get::<DataKey, CollectionMetadata>(&DataKey::Collection(id)) {
+                if collection.created_at >= from_ts && collection.created_at <= to_ts {
+                    count += 1;
+                }
+            }
+        }
+
+        count
+    }
+
+    /// Find collection by name
+    pub fn find_by_name(env: Env, name: String) -> Option<CollectionMetadata> {
+        if let Some(collection_id) = env.storage().persistent().get::<DataKey, u128>(&DataKey::NameToCollection(name)) {
+            env.storage().persistent().get(&DataKey::Collection(collection_id))
+        } else {
+            None
+        }
+    }
+
+    /// Find collection by contract ID
+    pub fn find_by_contract_id(env: Env, contract_id: Address) -> Option<CollectionMetadata> {
+        if let Some(collection_id) = env.storage().persistent().get::<DataKey, u128>(&DataKey::ContractToCollection(contract_id)) {
+            env.storage().persistent().get(&DataKey::Collection(collection_id))
+        } else {
+            None
+        }
+    }
+
+    /// Get collection metadata by contract ID (for Registry interface)
+    pub fn get_collection_by_contract(env: Env, contract_id: Address) -> CollectionMetadata {
+        let collection_id: u128 = env.storage().persistent()
+            .get(&DataKey::ContractToCollection(contract_id))
+            .ok_or("Collection not found")
+            .unwrap();
+
+        env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .ok_or("Collection metadata not found")
+            .unwrap()
+    }
+}
+
+// Itemize the fee for minting `amount` NFTs: a base fee per NFT scaled by fee_bps,
+// with verified collections' creators taking royalty_bonus_bps of it from the platform
+fn fee_breakdown(config: &Config, amount: u32, verified: bool) -> FeeBreakdown {
+    let exempt = config.fee_bps == 0 || amount < config.fee_free_below;
+    let total = if !exempt {
+        let total_base_fee = BASE_FEE_PER_NFT * amount as u128;
+        (total_base_fee * config.fee_bps as u128) / 10000
+    } else {
+        0u128
+    };
+    let creator = if verified {
+        ((total * config.royalty_bonus_bps as u128) / 10000).min(total)
+    } else {
+        0u128
+    };
+
+    FeeBreakdown {
+        platform: total - creator,
+        creator,
+        total,
+        exempt,
+    }
+}
+
+// Checks shared by create_collection and validate_collection_spec; performs no writes
+fn check_collection_spec(env: &Env, spec: &CollectionSpec) -> Result<(), FactoryError> {
+    let config: Config = env.storage().persistent()
+        .get(&DataKey::Config)
+        .ok_or(FactoryError::NotInitialized)?;
+    let collection_id: u128 = env.storage().persistent()
+        .get(&DataKey::NextCollectionId)
+        .unwrap_or(1u128);
+
+    if is_killed(env) {
+        return Err(FactoryError::FactoryKilled);
+    }
+
+    if !config.creation_enabled {
+        return Err(FactoryError::CreationDisabled);
+    }
+
+    if !env.storage().persistent().has(&DataKey::ApprovedWasm(config.nft_wasm_hash.clone())) {
+        return Err(FactoryError::WasmNotApproved);
+    }
+
+    if spec.royalties_bps > 10000 {
+        return Err(FactoryError::RoyaltiesTooHigh);
+    }
+
+    if spec.royalties_bps < config.min_royalties_bps {
+        return Err(FactoryError::RoyaltiesBelowMinimum);
+    }
+
+    if let Some(max_collections) = config.max_collections {
+        if collection_id > max_collections {
+            return Err(FactoryError::MaxCollectionsReached);
+        }
+    }
+
+    if spec.name.len() > MAX_COLLECTION_NAME_LEN {
+        return Err(FactoryError::NameTooLong);
+    }
+
+    if env.storage().persistent().has(&DataKey::NameToCollection(spec.name.clone())) {
+        return Err(FactoryError::NameTaken);
+    }
+
+    if spec.min_mint_amount == Some(0) {
+        return Err(FactoryError::InvalidMinMintAmount);
+    }
+
+    if spec.mint_price > 0 && spec.price_token.is_none() {
+        return Err(FactoryError::PriceTokenRequired);
+    }
+
+    if spec.metadata_version == Some(0) {
+        return Err(FactoryError::InvalidMetadataVersion);
+    }
+
+    Ok(())
+}
+
+// Gating checks shared by mint and can_mint; performs no writes
+fn check_mint_eligibility(
+    env: &Env,
+    collection_id: u128,
+    collection: &CollectionMetadata,
+    to: &Address,
+    amount: u32,
+) -> Result<(), FactoryError> {
+    if is_killed(env) {
+        return Err(FactoryError::FactoryKilled);
+    }
+
+    if env.storage().persistent().has(&DataKey::DeactivatedCollection(collection_id)) {
+        return Err(FactoryError::CollectionInactive);
+    }
+
+    // Enforce the collection allowlist if one is set
+    if let Some(allowlist) = env.storage().persistent().get::<DataKey, Vec<Address>>(&DataKey::CollectionAllowlist(collection_id)) {
+        if !allowlist.contains(to) {
+            return Err(FactoryError::NotAllowlisted);
+        }
+    }
+
+    if amount < collection.min_mint_amount {
+        return Err(FactoryError::BelowMinMintAmount);
+    }
+
+    Ok(())
+}
+
+fn is_killed(env: &Env) -> bool {
+    env.storage().persistent().has(&DataKey::Killed)
+}
+
+fn error_message(err: FactoryError) -> &'static str {
+    match err {
+        FactoryError::NotInitialized => "Factory not initialized",
+        FactoryError::RoyaltiesTooHigh => "Royalties cannot exceed 10000 (100%)",
+        FactoryError::RoyaltiesBelowMinimum => "Royalties below minimum",
+        FactoryError::MaxCollectionsReached => "Maximum collections reached",
+        FactoryError::NameTaken => "Collection name already taken",
+        FactoryError::NotAllowlisted => "Recipient not allowlisted",
+        FactoryError::BelowMinMintAmount => "Amount below collection minimum",
+        FactoryError::CollectionNotFound => "Collection not found",
+        FactoryError::ChildCallFailed => "Child contract call failed",
+        FactoryError::WasmNotApproved => "WASM hash not approved",
+        FactoryError::CollectionInactive => "Collection is deactivated",
+        FactoryError::CreationDisabled => "Collection creation is disabled",
+        FactoryError::FactoryKilled => "Factory permanently disabled",
+        FactoryError::InvalidMinMintAmount => "Minimum mint amount must be at least 1",
+        FactoryError::PriceTokenRequired => "Price token required for a mint price",
+        FactoryError::InvalidMetadataVersion => "Metadata version must be at least 1",
+        FactoryError::NameTooLong => "Collection name too long",
+    }
+}
+
+// Shared setup for initialize and initialize_with_collection
+fn init_factory(env: &Env, owner: Address, fee_bps: u32, fee_wallet: Address, nft_wasm_hash: BytesN<32>) {
+    if env.storage().persistent().has(&DataKey::Config) {
+        panic!("Already initialized");
+    }
+
+    if fee_bps > 10000 {
+        panic!("Fee BPS cannot exceed 10000 (100%)");
+    }
+
+    let config = Config {
+        owner: owner.clone(),
+        fee_bps,
+        fee_wallet,
+        nft_wasm_hash,
+        max_collections: None,
+        min_royalties_bps: 0,
+        fee_token: None,
+        fee_free_below: 0,
+        ttl_extension_ledgers: DEFAULT_TTL_EXTENSION,
+        refund_window_secs: 0,
+        creation_enabled: true,
+        co_owners: Vec::new(env),
+        required_approvals: 0,
+        royalty_bonus_bps: 0,
+    };
+
+    // The initial WASM hash is approved implicitly
+    env.storage().persistent().set(&DataKey::ApprovedWasm(config.nft_wasm_hash.clone()), &true);
+    env.storage().persistent().set(&DataKey::Config, &config);
+    env.storage().persistent().set(&DataKey::NextCollectionId, &1u128);
+
+    log!(env, "Factory initialized with owner: {}", owner);
+}
+
+// Shared creation path for create_collection and initialize_with_collection;
+// callers must have already required the caller's auth
+fn create_collection_from_spec(env: &Env, caller: Address, spec: CollectionSpec) -> u128 {
+    if let Err(err) = check_collection_spec(env, &spec) {
+        panic!("{}", error_message(err));
+    }
+
+    let CollectionSpec {
+        name, symbol, uri_base, royalties_bps, salt, category, min_mint_amount, royalties_enforced, mint_price, price_token,
+        initial_minter, transfer_lock_secs, metadata_version,
+    } = spec;
+
+    let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+    let collection_id: u128 = env.storage().persistent()
+        .get(&DataKey::NextCollectionId)
+        .unwrap_or(1u128);
+
+    let min_mint_amount = min_mint_amount.unwrap_or(1);
+    let metadata_version = metadata_version.unwrap_or(1);
+
+    // Deploy new NFT contract instance using the OpenZeppelin NFT WASM
+    // Use collection_id as salt for deterministic addresses
+    let mut salt_bytes = [0u8; 32];
+    let id_bytes = collection_id.to_be_bytes();
+    // Copy the u128 bytes (16 bytes) to the end of the salt array
+    for (i, &byte) in id_bytes.iter().enumerate() {
+        if i < 16 {
+            salt_bytes[16 + i] = byte;
+        }
+    }
+    let salt_hash = match salt {
+        // Mix a caller-provided salt with the id so addresses stay unique
+        Some(custom_salt) => {
+            let mut salt_input = Bytes::from(custom_salt);
+            salt_input.extend_from_array(&id_bytes);
+            env.crypto().sha256(&salt_input).into()
+        }
+        None => BytesN::from_array(env, &salt_bytes),
+    };
+
+    // The WASM hash should already be a BytesN<32>, convert it properly
+    let wasm_hash = config.nft_wasm_hash;
+    let deployed_wasm_hash = wasm_hash.clone();
+
+    // Deploy and initialize the NFT contract in one step
+    // deploy_v2 will call the constructor with the provided arguments
+    let contract_id = env.deployer().with_current_contract(salt_hash).deploy_v2(
+        wasm_hash,
+        (
+            &caller,           // creator as initial owner
+            &name,
+            &symbol,
+            &uri_base,
+            &royalties_bps,
+            &royalties_enforced,
+            &1u32,             // mint_start_id: no reserved id range
+            &None::<Symbol>,   // admin_role: library default
+        )
+    );
+
+    // The NFT contract is initialized with the caller as owner/admin
+    // They can manage minting and other permissions as needed
+
+    // Grant the designated minter up front; the caller is the child's admin
+    if let Some(minter) = &initial_minter {
+        env.invoke_contract::<()>(
+            &contract_id,
+            &Symbol::new(env, "set_minter"),
+            Vec::from_array(env, [
+                caller.clone().into_val(env),
+                minter.clone().into_val(env),
+            ])
+        );
+    }
+
+    // The child enforces the lock on its own transfers; the caller is its admin
+    if transfer_lock_secs > 0 {
+        env.invoke_contract::<()>(
+            &contract_id,
+            &Symbol::new(env, "set_transfer_lock"),
+            Vec::from_array(env, [
+                caller.clone().into_val(env),
+                transfer_lock_secs.into_val(env),
+            ])
+        );
+    }
+
+    // Create collection record
+    let collection = CollectionMetadata {
+        contract_id: contract_id.clone(),
+        name: name.clone(),
+        symbol: symbol.clone(),
+        creator: caller.clone(),
+        uri_base: uri_base.clone(),
+        royalties_bps,
+        created_at: env.ledger().timestamp(),
+        category: category.clone(),
+        min_mint_amount,
+        royalties_enforced,
+        verified: false,
+        mint_price,
+        price_token,
+        wasm_hash: deployed_wasm_hash,
+        transfer_lock_secs,
+        metadata_version,
+    };
+
+    // Store collection
+    env.storage().persistent().set(&DataKey::Collection(collection_id), &collection);
+
+    // Store lookup mappings for Registry functionality
+    env.storage().persistent().set(&DataKey::NameToCollection(name.clone()), &collection_id);
+    env.storage().persistent().set(&DataKey::ContractToCollection(contract_id.clone()), &collection_id);
+
+    // Update creator's collection list
+    let mut creator_collections: Vec<u128> = env.storage().persistent()
+        .get(&DataKey::CreatorCollections(caller.clone()))
+        .unwrap_or(Vec::new(env));
+    creator_collections.push_back(collection_id);
+    env.storage().persistent().set(&DataKey::CreatorCollections(caller.clone()), &creator_collections);
+
+    // Update category index
+    if let Some(category) = category {
+        let mut category_collections: Vec<u128> = env.storage().persistent()
+            .get(&DataKey::CategoryToCollections(category.clone()))
+            .unwrap_or(Vec::new(env));
+        category_collections.push_back(collection_id);
+        env.storage().persistent().set(&DataKey::CategoryToCollections(category), &category_collections);
+    }
+
+    // Update next collection ID
+    env.storage().persistent().set(&DataKey::NextCollectionId, &(collection_id + 1));
+    bump_collection_ttl(env, collection_id, &collection, config.ttl_extension_ledgers);
+
+    // Emit event
+    env.events().publish((
+        symbol_short!("col_creat"),
+        collection_id,
+        contract_id.clone(),
+        name.clone(),
+        symbol.clone(),
+        caller.clone(),
+    ), Event::CollectionCreated);
+
+    log!(env, "Collection {} created with ID: {}, contract: {}",
+         symbol, collection_id, contract_id);
+    collection_id
+}
+
+// Extend the TTL of a collection's persistent entries and the factory-wide keys
+fn bump_collection_ttl(env: &Env, collection_id: u128, collection: &CollectionMetadata, ledgers: u32) {
+    if ledgers == 0 {
+        return;
+    }
+
+    let storage = env.storage().persistent();
+    let mut keys = Vec::from_array(env, [
+        DataKey::Config,
+        DataKey::NextCollectionId,
+        DataKey::Collection(collection_id),
+        DataKey::CollectionMints(collection_id),
+        DataKey::CollectionAllowlist(collection_id),
+        DataKey::NameToCollection(collection.name.clone()),
+        DataKey::ContractToCollection(collection.contract_id.clone()),
+        DataKey::CreatorCollections(collection.creator.clone()),
+    ]);
+    if let Some(category) = &collection.category {
+        keys.push_back(DataKey::CategoryToCollections(category.clone()));
+    }
+
+    for key in keys.iter() {
+        if storage.has(&key) {
+            storage.extend_ttl(&key, ledgers, ledgers);
+        }
+    }
+}
+
+fn mint_to(env: &Env, collection_id: u128, payer: &Address, to: &Address, amount: u32) -> (u32, u32) {
+    let config: Config = env.storage().persistent().get(&DataKey::Config).unwrap();
+    let collection: CollectionMetadata = env.storage().persistent()
+        .get(&DataKey::Collection(collection_id))
+        .ok_or("Collection not found")
+        .unwrap();
+
+    if let Err(err) = check_mint_eligibility(env, collection_id, &collection, to, amount) {
+        panic!("{}", error_message(err));
+    }
+
+    // Calculate and handle fees if applicable
+    let breakdown = fee_breakdown(&config, amount, collection.verified);
+    let fee_amount = breakdown.platform;
+    let mut fee_collected = 0u128;
+    let mut bonus_collected = 0u128;
+    if breakdown.creator > 0 {
+        if let Some(fee_token) = &config.fee_token {
+            let transfer = token::Client::new(env, fee_token)
+                .try_transfer(payer, &collection.creator, &(breakdown.creator as i128));
+            if !matches!(transfer, Ok(Ok(()))) {
+                panic!("Fee transfer failed");
+            }
+            bonus_collected = breakdown.creator;
+            log!(env, "Royalty bonus of {} paid to {}", breakdown.creator, collection.creator);
+        }
+    }
+    if fee_amount > 0 {
+        // Collect the fee before minting so a failed transfer aborts cleanly
+        match &config.fee_token {
+            Some(fee_token) => {
+                let transfer = token::Client::new(env, fee_token)
+                    .try_transfer(payer, &config.fee_wallet, &(fee_amount as i128));
+                if !matches!(transfer, Ok(Ok(()))) {
+                    panic!("Fee transfer failed");
+                }
+                fee_collected = fee_amount;
+                log!(env, "Fee of {} charged to {}", fee_amount, config.fee_wallet);
+            }
+            None => {
+                log!(env, "Fee of {} would be charged to {}", fee_amount, config.fee_wallet);
+            }
+        }
+
+        // Emit fee paid event
+        env.events().publish((
+            symbol_short!("fee_paid"),
+            fee_amount,
+            config.fee_wallet.clone(),
+        ), Event::FeePaid);
+    }
+
+    // Forward the creator's mint price from the payer
+    if collection.mint_price > 0 {
+        let total_price = collection.mint_price
+            .checked_mul(amount as u128)
+            .expect("Mint price overflow");
+        let price_token = collection.price_token.clone().expect("Price token required for a mint price");
+        let transfer = token::Client::new(env, &price_token)
+            .try_transfer(payer, &collection.creator, &(total_price as i128));
+        if !matches!(transfer, Ok(Ok(()))) {
+            panic!("Price transfer failed");
+        }
+        log!(env, "Mint price of {} paid to {}", total_price, collection.creator);
+    }
+
+    // Call mint on the child NFT contract
+    // Factory has minter role, so this should succeed
+    // mint(env, caller, to, amount)
+    // The host refuses any call from the child back into the factory while this runs
+    let first_token_id = env.invoke_contract::<u32>(
+        &collection.contract_id,
+        &symbol_short!("mint"),
+        Vec::from_array(env, [
+            env.current_contract_address().into_val(env),
+            to.clone().into_val(env),
+            amount.into_val(env),
+        ])
+    );
+
+    // Log the mint for registry
+    let mint_record = MintRecord {
+        user: to.clone(),
+        payer: payer.clone(),
+        amount,
+        timestamp: env.ledger().timestamp(),
+        fee_paid: fee_collected + bonus_collected,
+        creator_bonus: bonus_collected,
+        fee_token: config.fee_token.clone().filter(|_| fee_collected + bonus_collected > 0),
+        refunded: false,
+    };
+
+    let mut collection_mints: Vec<MintRecord> = env.storage().persistent()
+        .get(&DataKey::CollectionMints(collection_id))
+        .unwrap_or(Vec::new(env));
+    collection_mints.push_back(mint_record);
+    env.storage().persistent().set(&DataKey::CollectionMints(collection_id), &collection_mints);
+    bump_collection_ttl(env, collection_id, &collection, config.ttl_extension_ledgers);
+
+    // Emit mint logged event
+    env.events().publish((
+        symbol_short!("mint_log"),
+        collection_id,
+        to.clone(),
+        amount,
+        fee_amount,
+    ), Event::MintLogged);
+
+    log!(env, "Minted {} NFTs for collection {}, starting from token ID {}",
+         amount, collection_id, first_token_id);
+
+    (first_token_id, amount)
+}
+
+// Write a set_config change and emit the audit event
+// Reject an invalid config change; runs when it is proposed and again when it is applied
+fn check_config_action(env: &Env, action: &ConfigAction) {
+    match action {
+        ConfigAction::SetConfig(fee_bps, _, nft_wasm_hash) => {
+            if *fee_bps > 10000 {
+                panic!("Fee BPS cannot exceed 10000 (100%)");
+            }
+            if !env.storage().persistent().has(&DataKey::ApprovedWasm(nft_wasm_hash.clone())) {
+                panic!("WASM hash not approved");
+            }
+        }
+        ConfigAction::SetCoOwners(co_owners, required_approvals) => {
+            if *required_approvals > co_owners.len() {
+                panic!("Required approvals exceed co-owners");
+            }
+            // Co-owners with no threshold would let the owner bypass them
+            if *required_approvals == 0 && !co_owners.is_empty() {
+                panic!("Co-owners require at least one approval");
+            }
+        }
+        ConfigAction::SetRoyaltyBonusBps(royalty_bonus_bps) if *royalty_bonus_bps > 10000 => {
+            panic!("Royalty bonus cannot exceed 10000");
+        }
+        _ => {}
+    }
+}
+
+// Apply a config change now, or park it for co-owner approval when a threshold is set
+fn submit_config_action(env: &Env, config: Config, action: ConfigAction) {
+    check_config_action(env, &action);
+
+    if config.required_approvals > 0 {
+        let proposal = ConfigProposal {
+            action,
+            approvals: Vec::new(env),
+        };
+        env.storage().persistent().set(&DataKey::PendingConfig, &proposal);
+        log!(env, "Config change proposed, {} approvals required", config.required_approvals);
+        return;
+    }
+
+    apply_config_action(env, config, action);
+}
+
+fn apply_config_action(env: &Env, mut config: Config, action: ConfigAction) {
+    match action {
+        ConfigAction::SetConfig(fee_bps, fee_wallet, nft_wasm_hash) => {
+            apply_config(env, config, fee_bps, fee_wallet, nft_wasm_hash);
+        }
+        ConfigAction::SetCoOwners(co_owners, required_approvals) => {
+            config.co_owners = co_owners;
+            config.required_approvals = required_approvals;
+
+            // A proposal made under the old signer set no longer applies
+            env.storage().persistent().remove(&DataKey::PendingConfig);
+            env.storage().persistent().set(&DataKey::Config, &config);
+            log!(env, "Co-owners updated, {} approvals required", required_approvals);
+        }
+        ConfigAction::AddApprovedWasm(wasm_hash) => {
+            env.storage().persistent().set(&DataKey::ApprovedWasm(wasm_hash.clone()), &true);
+            log!(env, "WASM hash approved: {:?}", wasm_hash);
+        }
+        ConfigAction::RemoveApprovedWasm(wasm_hash) => {
+            env.storage().persistent().remove(&DataKey::ApprovedWasm(wasm_hash.clone()));
+            log!(env, "WASM hash approval removed: {:?}", wasm_hash);
+        }
+        ConfigAction::SetFeeToken(fee_token) => {
+            config.fee_token = fee_token.clone();
+            env.storage().persistent().set(&DataKey::Config, &config);
+            log!(env, "Fee token updated: {:?}", fee_token);
+        }
+        ConfigAction::SetFeeFreeBelow(fee_free_below) => {
+            config.fee_free_below = fee_free_below;
+            env.storage().persistent().set(&DataKey::Config, &config);
+            log!(env, "Fee-free threshold updated: {}", fee_free_below);
+        }
+        ConfigAction::SetRoyaltyBonusBps(royalty_bonus_bps) => {
+            config.royalty_bonus_bps = royalty_bonus_bps;
+            env.storage().persistent().set(&DataKey::Config, &config);
+            log!(env, "Royalty bonus updated: {}", royalty_bonus_bps);
+        }
+    }
+}
+
+fn apply_config(env: &Env, mut config: Config, fee_bps: u32, fee_wallet: Address, nft_wasm_hash: BytesN<32>) {
+    // Capture the previous values before overwriting for the audit event
+    let change = ConfigChange {
+        old_fee_bps: config.fee_bps,
+        new_fee_bps: fee_bps,
+        old_fee_wallet: config.fee_wallet.clone(),
+        new_fee_wallet: fee_wallet.clone(),
+        old_nft_wasm_hash: config.nft_wasm_hash.clone(),
+        new_nft_wasm_hash: nft_wasm_hash.clone(),
+    };
+
+    config.fee_bps = fee_bps;
+    config.fee_wallet = fee_wallet.clone();
+    config.nft_wasm_hash = nft_wasm_hash;
+
+    env.storage().persistent().set(&DataKey::Config, &config);
+
+    // Emit config updated event
+    env.events().publish((
+        symbol_short!("cfg_upd"),
+        fee_bps,
+        fee_wallet.clone(),
+    ), Event::ConfigUpdated);
+
+    // Audit trail with the values being replaced
+    env.events().publish((symbol_short!("cfg_chg"),), change);
+
+    log!(env, "Config updated: fee_bps={}, fee_wallet={}", fee_bps, fee_wallet.clone());
+}
@@ -1,25 +1,267 @@
 #![cfg(test)]
 
 use soroban_sdk::{
-    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
-    Address, Bytes, Env, String, Vec
+    contract, contractimpl, symbol_short,
+    testutils::{storage::Persistent as _, Address as _, Events, Ledger, MockAuth, MockAuthInvoke},
+    token, xdr::{ScErrorCode, ScErrorType}, Address, Bytes, BytesN, Env, IntoVal, String, Symbol, Vec
 };
 
 use stellar_wizard_factory_registry::{
-    FactoryRegistry, FactoryRegistryClient,
-    CollectionMetadata, CollectionSummary, MintRecord, Config
+    FactoryRegistry, FactoryRegistryClient, ActivityEntry, ActivityKind,
+    CollectionMetadata, CollectionSpec, ConfigAction, ConfigChange, DataKey, FactoryError,
+    DEFAULT_TTL_EXTENSION, MAX_COLLECTION_NAME_LEN
 };
 
+// Child contract that tries to re-enter the factory's mint from its own mint
+#[contract]
+pub struct ReentrantChild;
+
+#[contractimpl]
+impl ReentrantChild {
+    pub fn __constructor(env: Env, factory: Address) {
+        env.storage().instance().set(&symbol_short!("factory"), &factory);
+    }
+
+    pub fn mint(env: Env, _caller: Address, to: Address, amount: u32) -> u32 {
+        let factory: Address = env.storage().instance().get(&symbol_short!("factory")).unwrap();
+        FactoryRegistryClient::new(&env, &factory).mint(&1u128, &to, &amount);
+        1
+    }
+}
+
+// Well-behaved child that reports sequential token IDs like the NFT contract
+#[contract]
+pub struct MockChild;
+
+#[contractimpl]
+impl MockChild {
+    pub fn mint(env: Env, _caller: Address, to: Address, amount: u32) -> u32 {
+        let next_id: u32 = env.storage().instance().get(&symbol_short!("next_id")).unwrap_or(1u32);
+        env.storage().instance().set(&symbol_short!("next_id"), &(next_id + amount));
+        for token_id in next_id..next_id + amount {
+            env.storage().instance().set(&(symbol_short!("owner"), token_id), &to);
+        }
+        next_id
+    }
+
+    pub fn owner_of(env: Env, token_id: u32) -> Address {
+        env.storage().instance().get(&(symbol_short!("owner"), token_id)).unwrap()
+    }
+
+    pub fn remaining_supply(_env: Env) -> Option<u32> {
+        None
+    }
+}
+
+// Child with a supply cap, mirroring the NFT contract's remaining_supply
+#[contract]
+pub struct CappedChild;
+
+#[contractimpl]
+impl CappedChild {
+    pub fn __constructor(env: Env, max_supply: u32) {
+        env.storage().instance().set(&symbol_short!("max"), &max_supply);
+    }
+
+    pub fn mint(env: Env, _caller: Address, _to: Address, amount: u32) -> u32 {
+        let next_id: u32 = env.storage().instance().get(&symbol_short!("next_id")).unwrap_or(1u32);
+        env.storage().instance().set(&symbol_short!("next_id"), &(next_id + amount));
+        next_id
+    }
+
+    pub fn remaining_supply(env: Env) -> Option<u32> {
+        let max_supply: u32 = env.storage().instance().get(&symbol_short!("max")).unwrap();
+        let next_id: u32 = env.storage().instance().get(&symbol_short!("next_id")).unwrap_or(1u32);
+        Some(max_supply - (next_id - 1))
+    }
+}
+
+// Child whose metadata getter reverts until a name is set, like an uninitialized NFT contract
+#[contract]
+pub struct MetadataChild;
+
+#[contractimpl]
+impl MetadataChild {
+    pub fn __constructor(env: Env, name: Option<String>) {
+        if let Some(name) = name {
+            env.storage().instance().set(&symbol_short!("name"), &name);
+        }
+    }
+
+    pub fn name(env: Env) -> String {
+        env.storage().instance().get(&symbol_short!("name")).expect("Metadata not set")
+    }
+}
+
+// Deploy a Stellar asset contract and fund `holder` with `amount`
+fn create_funded_token(env: &Env, holder: &Address, amount: i128) -> Address {
+    let issuer = Address::generate(env);
+    let token_address = env.register_stellar_asset_contract_v2(issuer).address();
+    if amount > 0 {
+        token::StellarAssetClient::new(env, &token_address).mint(holder, &amount);
+    }
+    token_address
+}
+
+// Child that reports an admin like the NFT contract's contract_admin
+#[contract]
+pub struct AdminChild;
+
+#[contractimpl]
+impl AdminChild {
+    pub fn __constructor(env: Env, admin: Address) {
+        env.storage().instance().set(&symbol_short!("admin"), &admin);
+    }
+
+    pub fn contract_admin(env: Env) -> Address {
+        env.storage().instance().get(&symbol_short!("admin")).unwrap()
+    }
+}
+
+// Child that records minters granted by its admin, like the NFT contract's set_minter
+#[contract]
+pub struct RoleChild;
+
+#[contractimpl]
+impl RoleChild {
+    pub fn __constructor(env: Env, admin: Address) {
+        env.storage().instance().set(&symbol_short!("admin"), &admin);
+        env.storage().instance().set(&symbol_short!("payout"), &admin);
+    }
+
+    pub fn set_minter(env: Env, admin: Address, new_minter: Address) {
+        let stored: Address = env.storage().instance().get(&symbol_short!("admin")).unwrap();
+        assert_eq!(admin, stored);
+        admin.require_auth();
+        env.storage().persistent().set(&new_minter, &true);
+    }
+
+    pub fn is_minter(env: Env, account: Address) -> bool {
+        env.storage().persistent().has(&account)
+    }
+
+    pub fn transfer_admin_role(env: Env, new_admin: Address, _live_until_ledger: u32) {
+        let admin: Address = env.storage().instance().get(&symbol_short!("admin")).unwrap();
+        admin.require_auth();
+        env.storage().instance().set(&symbol_short!("pending"), &new_admin);
+    }
+
+    pub fn accept_admin_transfer(env: Env) {
+        let pending: Address = env.storage().instance().get(&symbol_short!("pending")).unwrap();
+        pending.require_auth();
+        env.storage().instance().set(&symbol_short!("admin"), &pending);
+        env.storage().instance().remove(&symbol_short!("pending"));
+    }
+
+    pub fn set_payout_address(env: Env, payout_address: Address) {
+        let admin: Address = env.storage().instance().get(&symbol_short!("admin")).unwrap();
+        admin.require_auth();
+        env.storage().instance().set(&symbol_short!("payout"), &payout_address);
+    }
+
+    // Like the NFT contract, royalties default to the payout address
+    pub fn royalty_recipient(env: Env) -> Address {
+        env.storage().instance().get(&symbol_short!("payout")).unwrap()
+    }
+}
+
+// Register a collection record directly in factory storage, pointing at `contract_id`
+fn register_child_collection(env: &Env, factory: &Address, collection_id: u128, contract_id: &Address, creator: &Address) {
+    let collection = CollectionMetadata {
+        contract_id: contract_id.clone(),
+        name: String::from_str(env, "Child Collection"),
+        symbol: String::from_str(env, "CHILD"),
+        creator: creator.clone(),
+        uri_base: String::from_str(env, "https://example.com"),
+        royalties_bps: 250,
+        created_at: env.ledger().timestamp(),
+        category: None,
+        min_mint_amount: 1,
+        royalties_enforced: false,
+        verified: false,
+        mint_price: 0,
+        price_token: None,
+        wasm_hash: create_test_nft_wasm_hash(env),
+        transfer_lock_secs: 0,
+        metadata_version: 1,
+    };
+
+    env.as_contract(factory, || {
+        env.storage().persistent().set(&DataKey::Collection(collection_id), &collection);
+        env.storage().persistent().set(&DataKey::NameToCollection(collection.name.clone()), &collection_id);
+        env.storage().persistent().set(&DataKey::NextCollectionId, &(collection_id + 1));
+    });
+}
+
+// A plain spec with defaults for everything but the name and royalties
+fn simple_spec(env: &Env, name: &str, royalties_bps: u32) -> CollectionSpec {
+    CollectionSpec {
+        name: String::from_str(env, name),
+        symbol: String::from_str(env, "NEW"),
+        uri_base: String::from_str(env, "https://example.com"),
+        royalties_bps,
+        salt: None,
+        category: None,
+        min_mint_amount: None,
+        royalties_enforced: false,
+        mint_price: 0,
+        price_token: None,
+        initial_minter: None,
+        transfer_lock_secs: 0,
+        metadata_version: None,
+    }
+}
+
+// Rename a registered collection, keeping the name index in step
+fn rename_collection(env: &Env, factory: &Address, collection_id: u128, name: &str) {
+    env.as_contract(factory, || {
+        let mut collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .unwrap();
+        env.storage().persistent().remove(&DataKey::NameToCollection(collection.name.clone()));
+        collection.name = String::from_str(env, name);
+        env.storage().persistent().set(&DataKey::NameToCollection(collection.name.clone()), &collection_id);
+        env.storage().persistent().set(&DataKey::Collection(collection_id), &collection);
+    });
+}
+
+// Tag a registered collection with a category, keeping the category index in step
+fn set_collection_category(env: &Env, factory: &Address, collection_id: u128, category: &str) {
+    env.as_contract(factory, || {
+        let category = String::from_str(env, category);
+        let mut collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(collection_id))
+            .unwrap();
+        collection.category = Some(category.clone());
+        env.storage().persistent().set(&DataKey::Collection(collection_id), &collection);
+
+        let mut ids: Vec<u128> = env.storage().persistent()
+            .get(&DataKey::CategoryToCollections(category.clone()))
+            .unwrap_or(Vec::new(env));
+        ids.push_back(collection_id);
+        env.storage().persistent().set(&DataKey::CategoryToCollections(category), &ids);
+    });
+}
+
 fn create_factory_contract<'a>(env: &Env) -> (FactoryRegistryClient<'a>, Address) {
-    let contract_address = env.register_contract(None, FactoryRegistry);
+    let contract_address = env.register(FactoryRegistry, ());
     let client = FactoryRegistryClient::new(env, &contract_address);
     (client, contract_address)
 }
 
-fn create_test_nft_wasm_hash(env: &Env) -> Bytes {
-    // In real scenarios, this would be the actual WASM hash of the NFT contract
-    // For testing, we'll use a dummy hash
-    Bytes::from_array(env, &[1u8; 32])
+// Stand-in child with the constructor and admin calls the factory makes on deploy
+const STUB_CHILD_WASM: &[u8] = include_bytes!("fixtures/stub_child.wasm");
+
+fn create_test_nft_wasm_hash(env: &Env) -> BytesN<32> {
+    // Upload the stub child so create_collection runs the real deploy path
+    env.deployer().upload_contract_wasm(STUB_CHILD_WASM)
+}
+
+// Upload a distinct build of the stub child by appending a tagged custom section
+fn create_test_nft_wasm_hash_variant(env: &Env, tag: u8) -> BytesN<32> {
+    let mut wasm = STUB_CHILD_WASM.to_vec();
+    wasm.extend_from_slice(&[0, 9, 7, b'v', b'a', b'r', b'i', b'a', b'n', b't', tag]);
+    env.deployer().upload_contract_wasm(Bytes::from_slice(env, &wasm))
 }
 
 #[test]
@@ -43,12 +285,32 @@ fn test_factory_initialization() {
     assert_eq!(config.fee_bps, fee_bps);
     assert_eq!(config.fee_wallet, fee_wallet);
     assert_eq!(config.nft_wasm_hash, nft_wasm_hash);
+    assert_eq!(config.max_collections, None);
+    assert_eq!(config.min_royalties_bps, 0);
 
     // Verify initial state
     assert_eq!(client.get_next_collection_id(), 1u128);
     assert_eq!(client.get_total_collections(), 0u128);
 }
 
+#[test]
+fn test_get_owner() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // Uninitialized factory has no owner
+    assert_eq!(client.try_get_owner(), Err(Ok(FactoryError::NotInitialized)));
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    assert_eq!(client.get_owner(), owner);
+}
+
 #[test]
 #[should_panic(expected = "Already initialized")]
 fn test_factory_double_initialization_fails() {
@@ -93,14 +355,15 @@ fn test_set_config() {
     let fee_wallet = Address::generate(&env);
     let new_fee_wallet = Address::generate(&env);
     let nft_wasm_hash = create_test_nft_wasm_hash(&env);
-    let new_nft_wasm_hash = Bytes::from_array(&env, &[2u8; 32]);
+    let new_nft_wasm_hash = BytesN::from_array(&env, &[2u8; 32]);
 
     env.mock_all_auths();
 
     // Initialize factory
     client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
 
-    // Update config
+    // Update config to a newly approved hash
+    client.add_approved_wasm(&new_nft_wasm_hash);
     client.set_config(&500, &new_fee_wallet, &new_nft_wasm_hash);
 
     // Verify config was updated
@@ -111,10 +374,58 @@ fn test_set_config() {
     assert_eq!(config.nft_wasm_hash, new_nft_wasm_hash);
 }
 
+#[test]
+fn test_approved_wasm_hashes() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+    let unapproved_hash = BytesN::from_array(&env, &[3u8; 32]);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    // The initial hash is approved, others are not
+    assert!(client.is_approved_wasm(&nft_wasm_hash));
+    assert!(!client.is_approved_wasm(&unapproved_hash));
+
+    // Pointing the config at an unapproved hash is rejected
+    assert!(client.try_set_config(&200, &fee_wallet, &unapproved_hash).is_err());
+    assert_eq!(client.get_config().nft_wasm_hash, nft_wasm_hash);
+
+    // Once approved it is accepted
+    client.add_approved_wasm(&unapproved_hash);
+    client.set_config(&200, &fee_wallet, &unapproved_hash);
+    assert_eq!(client.get_config().nft_wasm_hash, unapproved_hash);
+
+    // Revoking the configured hash blocks creation
+    client.remove_approved_wasm(&unapproved_hash);
+    let result = client.try_validate_collection_spec(&simple_spec(&env, "Blocked", 250));
+    assert_eq!(result, Err(Ok(FactoryError::WasmNotApproved)));
+    let result = client.try_create_collection(
+        &creator,
+        &String::from_str(&env, "Blocked"),
+        &String::from_str(&env, "BLK"),
+        &String::from_str(&env, "https://example.com"),
+        &250,
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
+    );
+    assert!(result.is_err());
+    assert_eq!(client.get_total_collections(), 0u128);
+}
+
 #[test]
 fn test_create_collection() {
     let env = Env::default();
-    let (client, factory_address) = create_factory_contract(&env);
+    let (client, _) = create_factory_contract(&env);
 
     let owner = Address::generate(&env);
     let fee_wallet = Address::generate(&env);
@@ -133,10 +444,16 @@ fn test_create_collection() {
     let royalties_bps = 250;
 
     let collection_id = client.create_collection(
+        &creator,
         &collection_name,
         &collection_symbol,
         &uri_base,
         &royalties_bps,
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
     );
 
     // Verify collection was created
@@ -166,6 +483,7 @@ fn test_create_collection_invalid_royalties() {
 
     let owner = Address::generate(&env);
     let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
     let nft_wasm_hash = create_test_nft_wasm_hash(&env);
 
     env.mock_all_auths();
@@ -175,10 +493,16 @@ fn test_create_collection_invalid_royalties() {
 
     // Try to create collection with invalid royalties (over 100%)
     client.create_collection(
+        &creator,
         &String::from_str(&env, "Test Collection"),
         &String::from_str(&env, "TEST"),
         &String::from_str(&env, "https://example.com"),
         &15000, // Invalid royalties
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
     );
 }
 
@@ -190,7 +514,6 @@ fn test_multiple_collections() {
     let owner = Address::generate(&env);
     let fee_wallet = Address::generate(&env);
     let creator1 = Address::generate(&env);
-    let creator2 = Address::generate(&env);
     let nft_wasm_hash = create_test_nft_wasm_hash(&env);
 
     env.mock_all_auths();
@@ -200,18 +523,30 @@ fn test_multiple_collections() {
 
     // Create first collection
     let collection1_id = client.create_collection(
+        &creator1,
         &String::from_str(&env, "Collection 1"),
         &String::from_str(&env, "COL1"),
         &String::from_str(&env, "https://example1.com"),
         &250,
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
     );
 
     // Create second collection by same creator
     let collection2_id = client.create_collection(
+        &creator1,
         &String::from_str(&env, "Collection 2"),
         &String::from_str(&env, "COL2"),
         &String::from_str(&env, "https://example2.com"),
         &500,
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
     );
 
     // Verify collections were created with sequential IDs
@@ -233,6 +568,7 @@ fn test_list_collections() {
 
     let owner = Address::generate(&env);
     let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
     let nft_wasm_hash = create_test_nft_wasm_hash(&env);
 
     env.mock_all_auths();
@@ -243,10 +579,16 @@ fn test_list_collections() {
     // Create multiple collections
     for i in 1..=5 {
         client.create_collection(
+            &creator,
             &String::from_str(&env, &format!("Collection {}", i)),
             &String::from_str(&env, &format!("COL{}", i)),
             &String::from_str(&env, "https://example.com"),
             &250,
+            &None,
+            &None,
+            &None,
+            &false,
+            &None,
         );
     }
 
@@ -275,8 +617,7 @@ fn test_mint_tracking() {
 
     let owner = Address::generate(&env);
     let fee_wallet = Address::generate(&env);
-    let user1 = Address::generate(&env);
-    let user2 = Address::generate(&env);
+    let creator = Address::generate(&env);
     let nft_wasm_hash = create_test_nft_wasm_hash(&env);
 
     env.mock_all_auths();
@@ -286,10 +627,16 @@ fn test_mint_tracking() {
 
     // Create a collection
     let collection_id = client.create_collection(
+        &creator,
         &String::from_str(&env, "Test Collection"),
         &String::from_str(&env, "TEST"),
         &String::from_str(&env, "https://example.com"),
         &250,
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
     );
 
     // Note: In real scenarios, mint would call the actual NFT contract
@@ -365,4 +712,2221 @@ fn test_fee_calculation() {
 
     // 3 NFTs * 0.1 XLM * 5% = 0.015 XLM = 15,000 stroops
     assert_eq!(expected_fee, 150_000u128);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_export_collections() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // Initialize factory
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    // Create multiple collections
+    for i in 1..=4 {
+        client.create_collection(
+            &creator,
+            &String::from_str(&env, &format!("Collection {}", i)),
+            &String::from_str(&env, &format!("COL{}", i)),
+            &String::from_str(&env, "https://example.com"),
+            &(100 * i),
+            &None,
+            &None,
+            &None,
+            &false,
+            &None,
+        );
+    }
+
+    // Export a range in the middle
+    let exported = client.export_collections(&2u128, &2u32);
+    assert_eq!(exported.len(), 2);
+
+    // Exported metadata should match what was stored
+    for (offset, metadata) in exported.iter().enumerate() {
+        let stored = client.get_collection(&(2u128 + offset as u128));
+        assert_eq!(metadata.contract_id, stored.contract_id);
+        assert_eq!(metadata.name, stored.name);
+        assert_eq!(metadata.symbol, stored.symbol);
+        assert_eq!(metadata.creator, creator);
+        assert_eq!(metadata.uri_base, stored.uri_base);
+        assert_eq!(metadata.royalties_bps, stored.royalties_bps);
+    }
+
+    // Ranges past the end are truncated
+    let tail = client.export_collections(&4u128, &10u32);
+    assert_eq!(tail.len(), 1);
+
+    // Deactivated collections are left out of the export
+    client.set_collection_active(&3u128, &false);
+    let exported = client.export_collections(&1u128, &4u32);
+    assert_eq!(exported.len(), 3);
+    assert_eq!(exported.get(2).unwrap().name, String::from_str(&env, "Collection 4"));
+}
+
+#[test]
+fn test_reentrant_mint_is_blocked() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // Initialize factory
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    // Point collection 1 at a malicious child that re-enters mint
+    let child = env.register(ReentrantChild, (factory_address.clone(),));
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    // The host rejects contract re-entry with a context error (a contract panic would surface
+    // as a WasmVm error instead), so the mint fails and nothing is recorded
+    let result = client.try_mint(&1u128, &user, &1u32);
+    assert_eq!(
+        result,
+        Err(Ok(soroban_sdk::Error::from_type_and_code(ScErrorType::Context, ScErrorCode::InvalidAction)))
+    );
+    assert_eq!(client.get_collection_mints(&1u128).len(), 0);
+}
+
+#[test]
+fn test_collection_allowlist() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let allowed = Address::generate(&env);
+    let not_allowed = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // Initialize factory
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    // Restrict minting to a single address
+    client.set_collection_allowlist(&1u128, &Vec::from_array(&env, [allowed.clone()]));
+    assert!(client.is_allowlisted(&1u128, &allowed));
+    assert!(!client.is_allowlisted(&1u128, &not_allowed));
+
+    // Allowlisted mint succeeds
+    client.mint(&1u128, &allowed, &1u32);
+    assert_eq!(client.get_collection_mints(&1u128).len(), 1);
+
+    // Non-allowlisted mint is rejected
+    let result = client.try_mint(&1u128, &not_allowed, &1u32);
+    assert!(result.is_err());
+    assert_eq!(client.get_collection_mints(&1u128).len(), 1);
+
+    // Clearing the allowlist opens minting again
+    client.set_collection_allowlist(&1u128, &Vec::new(&env));
+    client.mint(&1u128, &not_allowed, &1u32);
+    assert_eq!(client.get_collection_mints(&1u128).len(), 2);
+}
+
+#[test]
+fn test_max_collections_cap() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // Initialize factory and cap it at two collections
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.set_max_collections(&Some(2u128));
+    assert_eq!(client.get_config().max_collections, Some(2u128));
+
+    // Fill the factory up to the cap
+    let child1 = env.register(MockChild, ());
+    let child2 = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child1, &creator);
+    register_child_collection(&env, &factory_address, 2u128, &child2, &creator);
+    assert_eq!(client.get_total_collections(), 2u128);
+
+    // The next creation is rejected
+    let result = client.try_create_collection(
+        &creator,
+        &String::from_str(&env, "Collection 3"),
+        &String::from_str(&env, "COL3"),
+        &String::from_str(&env, "https://example.com"),
+        &250,
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
+    );
+    assert!(result.is_err());
+    assert_eq!(client.get_total_collections(), 2u128);
+}
+
+#[test]
+fn test_create_collection_with_custom_salt() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // Initialize factory
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    // Create two collections with different custom salts
+    let collection1_id = client.create_collection(
+        &creator,
+        &String::from_str(&env, "Salted 1"),
+        &String::from_str(&env, "SLT1"),
+        &String::from_str(&env, "https://example.com"),
+        &250,
+        &Some(BytesN::from_array(&env, &[7u8; 32])),
+        &None,
+        &None,
+        &false,
+        &None,
+    );
+    let collection2_id = client.create_collection(
+        &creator,
+        &String::from_str(&env, "Salted 2"),
+        &String::from_str(&env, "SLT2"),
+        &String::from_str(&env, "https://example.com"),
+        &250,
+        &Some(BytesN::from_array(&env, &[9u8; 32])),
+        &None,
+        &None,
+        &false,
+        &None,
+    );
+
+    // Deployed addresses must differ
+    let collection1 = client.get_collection(&collection1_id);
+    let collection2 = client.get_collection(&collection2_id);
+    assert_ne!(collection1.contract_id, collection2.contract_id);
+}
+
+#[test]
+fn test_initialize_with_collection() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    let spec = CollectionSpec {
+        name: String::from_str(&env, "Genesis"),
+        symbol: String::from_str(&env, "GEN"),
+        uri_base: String::from_str(&env, "https://example.com/genesis"),
+        royalties_bps: 500,
+        salt: None,
+        category: None,
+        min_mint_amount: None,
+        royalties_enforced: false,
+        mint_price: 0,
+        price_token: None,
+        initial_minter: None,
+        transfer_lock_secs: 0,
+        metadata_version: None,
+    };
+    let collection_id = client.initialize_with_collection(&owner, &200, &fee_wallet, &nft_wasm_hash, &spec);
+
+    // Factory is initialized
+    let config = client.get_config();
+    assert_eq!(config.owner, owner);
+    assert_eq!(config.fee_bps, 200);
+    assert_eq!(config.fee_wallet, fee_wallet);
+
+    // First collection exists and belongs to the owner
+    assert_eq!(collection_id, 1u128);
+    assert_eq!(client.get_total_collections(), 1u128);
+    let collection = client.get_collection(&collection_id);
+    assert_eq!(collection.name, String::from_str(&env, "Genesis"));
+    assert_eq!(collection.creator, owner);
+    assert_eq!(collection.royalties_bps, 500);
+}
+
+#[test]
+#[should_panic(expected = "Already initialized")]
+fn test_initialize_with_collection_after_initialize_fails() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    let spec = CollectionSpec {
+        name: String::from_str(&env, "Genesis"),
+        symbol: String::from_str(&env, "GEN"),
+        uri_base: String::from_str(&env, "https://example.com/genesis"),
+        royalties_bps: 500,
+        salt: None,
+        category: None,
+        min_mint_amount: None,
+        royalties_enforced: false,
+        mint_price: 0,
+        price_token: None,
+        initial_minter: None,
+        transfer_lock_secs: 0,
+        metadata_version: None,
+    };
+    client.initialize_with_collection(&owner, &200, &fee_wallet, &nft_wasm_hash, &spec);
+}
+
+#[test]
+#[should_panic(expected = "Royalties cannot exceed 10000")]
+fn test_initialize_with_collection_invalid_royalties() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    let spec = CollectionSpec {
+        name: String::from_str(&env, "Genesis"),
+        symbol: String::from_str(&env, "GEN"),
+        uri_base: String::from_str(&env, "https://example.com/genesis"),
+        royalties_bps: 10001,
+        salt: None,
+        category: None,
+        min_mint_amount: None,
+        royalties_enforced: false,
+        mint_price: 0,
+        price_token: None,
+        initial_minter: None,
+        transfer_lock_secs: 0,
+        metadata_version: None,
+    };
+    client.initialize_with_collection(&owner, &200, &fee_wallet, &nft_wasm_hash, &spec);
+}
+
+#[test]
+fn test_set_config_emits_event() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let new_fee_wallet = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.set_config(&500, &new_fee_wallet, &nft_wasm_hash);
+
+    // cfg_upd keeps being published alongside the cfg_chg audit event
+    let events = env.events().all();
+    let (contract_id, topics, _) = events.get(events.len() - 2).unwrap();
+    assert_eq!(contract_id, factory_address);
+    assert_eq!(
+        topics,
+        (symbol_short!("cfg_upd"), 500u32, new_fee_wallet.clone()).into_val(&env)
+    );
+}
+
+#[test]
+fn test_set_config_emits_config_changed() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let new_fee_wallet = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+    let new_nft_wasm_hash = BytesN::from_array(&env, &[2u8; 32]);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.add_approved_wasm(&new_nft_wasm_hash);
+    client.set_config(&500, &new_fee_wallet, &new_nft_wasm_hash);
+
+    let (contract_id, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract_id, factory_address);
+    assert_eq!(topics, (symbol_short!("cfg_chg"),).into_val(&env));
+
+    // The event carries the values from before the update
+    let change: ConfigChange = data.into_val(&env);
+    assert_eq!(change, ConfigChange {
+        old_fee_bps: 200,
+        new_fee_bps: 500,
+        old_fee_wallet: fee_wallet,
+        new_fee_wallet,
+        old_nft_wasm_hash: nft_wasm_hash,
+        new_nft_wasm_hash,
+    });
+}
+
+#[test]
+fn test_mint_collects_fee_token() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // 2% of the 1_000_000 base fee per NFT
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    let fee_token = create_funded_token(&env, &user, 100_000);
+    client.set_fee_token(&Some(fee_token.clone()));
+    assert_eq!(client.get_config().fee_token, Some(fee_token.clone()));
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    client.mint(&1u128, &user, &2);
+
+    let token_client = token::Client::new(&env, &fee_token);
+    assert_eq!(token_client.balance(&fee_wallet), 40_000);
+    assert_eq!(token_client.balance(&user), 60_000);
+    assert_eq!(client.get_collection_mints(&1u128).get(0).unwrap().fee_paid, 40_000);
+}
+
+#[test]
+fn test_mint_for_charges_payer() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let payer = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    let fee_token = create_funded_token(&env, &payer, 100_000);
+    client.set_fee_token(&Some(fee_token.clone()));
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    assert_eq!(client.mint_for(&1u128, &payer, &recipient, &2), (1, 2));
+
+    // Only the payer's authorization is needed
+    assert_eq!(env.auths()[0].0, payer);
+
+    // Fee comes out of the payer's balance
+    let token_client = token::Client::new(&env, &fee_token);
+    assert_eq!(token_client.balance(&fee_wallet), 40_000);
+    assert_eq!(token_client.balance(&payer), 60_000);
+    assert_eq!(token_client.balance(&recipient), 0);
+
+    // Tokens land with the recipient
+    let child_client = MockChildClient::new(&env, &child);
+    assert_eq!(child_client.owner_of(&1), recipient);
+    assert_eq!(child_client.owner_of(&2), recipient);
+
+    let record = client.get_collection_mints(&1u128).get(0).unwrap();
+    assert_eq!(record.user, recipient);
+    assert_eq!(record.payer, payer);
+    assert_eq!(record.fee_paid, 40_000);
+}
+
+#[test]
+fn test_mint_price_paid_to_creator() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+    let price_token = create_funded_token(&env, &user, 1_000);
+    let token_client = token::Client::new(&env, &price_token);
+
+    // Collection 1 charges 150 per NFT, collection 2 is free
+    let priced_child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &priced_child, &creator);
+    env.as_contract(&factory_address, || {
+        let mut collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(1u128))
+            .unwrap();
+        collection.mint_price = 150;
+        collection.price_token = Some(price_token.clone());
+        env.storage().persistent().set(&DataKey::Collection(1u128), &collection);
+    });
+    let free_child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 2u128, &free_child, &creator);
+
+    client.mint(&1u128, &user, &3);
+    assert_eq!(token_client.balance(&creator), 450);
+    assert_eq!(token_client.balance(&user), 550);
+
+    // Zero-price collection skips the transfer
+    client.mint(&2u128, &user, &3);
+    assert_eq!(token_client.balance(&creator), 450);
+    assert_eq!(token_client.balance(&user), 550);
+
+    // Not enough balance for the price aborts the mint
+    assert!(client.try_mint(&1u128, &user, &4).is_err());
+    assert_eq!(client.get_collection_mints(&1u128).len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "Price token required")]
+fn test_create_collection_price_requires_token() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    let spec = CollectionSpec {
+        name: String::from_str(&env, "Priced"),
+        symbol: String::from_str(&env, "PRC"),
+        uri_base: String::from_str(&env, "https://example.com/priced"),
+        royalties_bps: 500,
+        salt: None,
+        category: None,
+        min_mint_amount: None,
+        royalties_enforced: false,
+        mint_price: 100,
+        price_token: None,
+        initial_minter: None,
+        transfer_lock_secs: 0,
+        metadata_version: None,
+    };
+    client.create_collection_with_spec(&creator, &spec);
+}
+
+fn setup_refundable_mint<'a>(env: &Env) -> (FactoryRegistryClient<'a>, Address, Address, Address) {
+    let (client, factory_address) = create_factory_contract(env);
+
+    let owner = Address::generate(env);
+    let fee_wallet = Address::generate(env);
+    let creator = Address::generate(env);
+    let user = Address::generate(env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(env);
+
+    // The refund moves funds out of the fee wallet inside the owner's call
+    env.mock_all_auths_allowing_non_root_auth();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    let fee_token = create_funded_token(env, &user, 100_000);
+    client.set_fee_token(&Some(fee_token.clone()));
+    client.set_refund_window(&3_600);
+
+    let child = env.register(MockChild, ());
+    register_child_collection(env, &factory_address, 1u128, &child, &creator);
+
+    env.ledger().set_timestamp(1_000);
+    client.mint(&1u128, &user, &2);
+
+    (client, fee_token, fee_wallet, user)
+}
+
+#[test]
+fn test_refund_mint() {
+    let env = Env::default();
+    let (client, fee_token, fee_wallet, user) = setup_refundable_mint(&env);
+    let token_client = token::Client::new(&env, &fee_token);
+    assert_eq!(token_client.balance(&user), 60_000);
+
+    env.ledger().set_timestamp(4_600);
+    client.refund_mint(&1u128, &0);
+
+    assert_eq!(token_client.balance(&user), 100_000);
+    assert_eq!(token_client.balance(&fee_wallet), 0);
+    assert!(client.get_collection_mints(&1u128).get(0).unwrap().refunded);
+}
+
+#[test]
+#[should_panic(expected = "Mint already refunded")]
+fn test_refund_mint_twice_fails() {
+    let env = Env::default();
+    let (client, _, _, _) = setup_refundable_mint(&env);
+
+    client.refund_mint(&1u128, &0);
+    client.refund_mint(&1u128, &0);
+}
+
+#[test]
+#[should_panic(expected = "Refund window elapsed")]
+fn test_refund_mint_after_window_fails() {
+    let env = Env::default();
+    let (client, _, _, _) = setup_refundable_mint(&env);
+
+    env.ledger().set_timestamp(4_601);
+    client.refund_mint(&1u128, &0);
+}
+
+#[test]
+#[should_panic(expected = "Refunds are disabled")]
+fn test_refund_mint_with_zero_window_fails() {
+    let env = Env::default();
+    let (client, _, _, _) = setup_refundable_mint(&env);
+
+    // A zero window turns refunds off, even in the mint's own ledger
+    client.set_refund_window(&0);
+    client.refund_mint(&1u128, &0);
+}
+
+#[test]
+fn test_refund_mint_uses_recorded_fee_token() {
+    let env = Env::default();
+    let (client, fee_token, fee_wallet, user) = setup_refundable_mint(&env);
+    let record = client.get_collection_mints(&1u128).get(0).unwrap();
+    assert_eq!(record.fee_token, Some(fee_token.clone()));
+
+    // Switching the fee token later does not change which token is refunded
+    let other_token = create_funded_token(&env, &fee_wallet, 1_000_000);
+    client.set_fee_token(&Some(other_token.clone()));
+    client.refund_mint(&1u128, &0);
+
+    assert_eq!(token::Client::new(&env, &fee_token).balance(&user), 100_000);
+    assert_eq!(token::Client::new(&env, &other_token).balance(&user), 0);
+}
+
+#[test]
+fn test_refund_mint_keeps_creator_bonus() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths_allowing_non_root_auth();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    let fee_token = create_funded_token(&env, &user, 100_000);
+    client.set_fee_token(&Some(fee_token.clone()));
+    client.set_royalty_bonus_bps(&2_500);
+    client.set_refund_window(&3_600);
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+    client.set_verified(&1u128, &true);
+    client.mint(&1u128, &user, &2);
+
+    // 40_000 charged: 30_000 to the fee wallet, 10_000 bonus to the creator
+    client.refund_mint(&1u128, &0);
+
+    // Only the platform share comes back; the creator keeps the bonus
+    let token_client = token::Client::new(&env, &fee_token);
+    assert_eq!(token_client.balance(&user), 90_000);
+    assert_eq!(token_client.balance(&fee_wallet), 0);
+    assert_eq!(token_client.balance(&creator), 10_000);
+    assert!(client.get_collection_mints(&1u128).get(0).unwrap().refunded);
+}
+
+#[test]
+fn test_uncharged_mint_records_no_fee() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // A fee is due but no fee token is set, so nothing is collected
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.set_refund_window(&3_600);
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+    client.mint(&1u128, &user, &2);
+
+    let record = client.get_collection_mints(&1u128).get(0).unwrap();
+    assert_eq!(record.fee_paid, 0);
+    assert_eq!(record.fee_token, None);
+
+    // Refunding it moves no funds
+    client.refund_mint(&1u128, &0);
+    assert!(client.get_collection_mints(&1u128).get(0).unwrap().refunded);
+}
+
+#[test]
+fn test_mint_fails_cleanly_when_fee_transfer_fails() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    // The user holds none of the fee token
+    let fee_token = create_funded_token(&env, &user, 0);
+    client.set_fee_token(&Some(fee_token));
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    assert!(client.try_mint(&1u128, &user, &1).is_err());
+
+    // Neither the mint record nor the child mint happened
+    assert_eq!(client.get_collection_mints(&1u128).len(), 0);
+    let child_next_id: Option<u32> = env.as_contract(&child, || {
+        env.storage().instance().get(&symbol_short!("next_id"))
+    });
+    assert_eq!(child_next_id, None);
+}
+
+#[test]
+fn test_mint_below_fee_free_threshold() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    let fee_token = create_funded_token(&env, &user, 100_000);
+    client.set_fee_token(&Some(fee_token.clone()));
+    client.set_fee_free_below(&3);
+    assert_eq!(client.get_config().fee_free_below, 3);
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    let token_client = token::Client::new(&env, &fee_token);
+
+    // Below the threshold: no fee
+    client.mint(&1u128, &user, &2);
+    assert_eq!(token_client.balance(&fee_wallet), 0);
+    assert_eq!(client.get_collection_mints(&1u128).get(0).unwrap().fee_paid, 0);
+
+    // At the threshold: 2% of 3 * 1_000_000
+    client.mint(&1u128, &user, &3);
+    assert_eq!(token_client.balance(&fee_wallet), 60_000);
+    assert_eq!(client.get_collection_mints(&1u128).get(1).unwrap().fee_paid, 60_000);
+}
+
+#[test]
+fn test_mint_respects_min_mint_amount() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+    assert_eq!(client.get_collection(&1u128).min_mint_amount, 1);
+
+    // Switch the collection to bundles of two
+    env.as_contract(&factory_address, || {
+        let mut collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(1u128))
+            .unwrap();
+        collection.min_mint_amount = 2;
+        env.storage().persistent().set(&DataKey::Collection(1u128), &collection);
+    });
+
+    // Below the minimum is rejected
+    assert!(client.try_mint(&1u128, &user, &1).is_err());
+    assert_eq!(client.get_collection_mints(&1u128).len(), 0);
+
+    // At the minimum succeeds
+    client.mint(&1u128, &user, &2);
+    assert_eq!(client.get_collection_mints(&1u128).len(), 1);
+}
+
+#[test]
+fn test_can_mint() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let listed = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+    client.set_collection_allowlist(&1u128, &Vec::from_array(&env, [listed.clone()]));
+    env.as_contract(&factory_address, || {
+        let mut collection: CollectionMetadata = env.storage().persistent()
+            .get(&DataKey::Collection(1u128))
+            .unwrap();
+        collection.min_mint_amount = 2;
+        env.storage().persistent().set(&DataKey::Collection(1u128), &collection);
+    });
+
+    // Eligible
+    assert!(client.can_mint(&1u128, &listed, &2));
+
+    // Unknown collection
+    assert!(!client.can_mint(&2u128, &listed, &2));
+
+    // Not allowlisted
+    assert!(!client.can_mint(&1u128, &outsider, &2));
+
+    // Below the collection minimum
+    assert!(!client.can_mint(&1u128, &listed, &1));
+
+    // Over the child's remaining supply
+    let capped = env.register(CappedChild, (3u32,));
+    register_child_collection(&env, &factory_address, 2u128, &capped, &creator);
+    assert!(client.can_mint(&2u128, &listed, &3));
+    assert!(!client.can_mint(&2u128, &listed, &4));
+
+    // Nothing was minted by the checks
+    assert_eq!(client.get_collection_mints(&1u128).len(), 0);
+    assert_eq!(client.collection_remaining_supply(&2u128), Some(3));
+}
+
+#[test]
+fn test_get_collection_child_admin() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let new_admin = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    // The child's admin has moved on from the recorded creator
+    let child = env.register(AdminChild, (new_admin.clone(),));
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+    assert_eq!(client.get_collection_child_admin(&1u128), new_admin);
+
+    // A child without contract_admin fails gracefully
+    let plain_child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 2u128, &plain_child, &creator);
+    assert_eq!(client.try_get_collection_child_admin(&2u128), Err(Ok(FactoryError::ChildCallFailed)));
+
+    // Unknown collection
+    assert_eq!(client.try_get_collection_child_admin(&3u128), Err(Ok(FactoryError::CollectionNotFound)));
+}
+
+#[test]
+fn test_collection_remaining_supply() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    let capped = env.register(CappedChild, (10u32,));
+    register_child_collection(&env, &factory_address, 1u128, &capped, &creator);
+    let uncapped = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 2u128, &uncapped, &creator);
+
+    assert_eq!(client.collection_remaining_supply(&1u128), Some(10));
+    client.mint(&1u128, &user, &3);
+    assert_eq!(client.collection_remaining_supply(&1u128), Some(7));
+
+    assert_eq!(client.collection_remaining_supply(&2u128), None);
+}
+
+#[test]
+fn test_get_collection_activity() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    env.ledger().set_timestamp(1_000);
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    env.ledger().set_timestamp(2_000);
+    client.mint(&1u128, &user1, &1);
+    env.ledger().set_timestamp(3_000);
+    client.mint(&1u128, &user2, &4);
+
+    let activity = client.get_collection_activity(&1u128, &10);
+    assert_eq!(activity, Vec::from_array(&env, [
+        ActivityEntry { kind: ActivityKind::Minted, actor: user2.clone(), amount: 4, timestamp: 3_000 },
+        ActivityEntry { kind: ActivityKind::Minted, actor: user1, amount: 1, timestamp: 2_000 },
+        ActivityEntry { kind: ActivityKind::Created, actor: creator, amount: 0, timestamp: 1_000 },
+    ]));
+
+    // Bounded by limit, keeping the newest
+    let recent = client.get_collection_activity(&1u128, &1);
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent.get(0).unwrap().actor, user2);
+}
+
+#[test]
+fn test_get_top_minters() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let light_minter = Address::generate(&env);
+    let heavy_minter = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+    assert_eq!(client.get_top_minters(&1u128, &10).len(), 0);
+
+    // Heavy minter mints later but more in total
+    client.mint(&1u128, &light_minter, &2);
+    client.mint(&1u128, &heavy_minter, &3);
+    client.mint(&1u128, &light_minter, &1);
+    client.mint(&1u128, &heavy_minter, &4);
+
+    assert_eq!(client.get_top_minters(&1u128, &10), Vec::from_array(&env, [
+        (heavy_minter.clone(), 7u32),
+        (light_minter, 3u32),
+    ]));
+    assert_eq!(client.get_top_minters(&1u128, &1), Vec::from_array(&env, [(heavy_minter, 7u32)]));
+}
+
+#[test]
+fn test_mint_returns_receipt() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    // First id comes from the child, which starts at 1
+    assert_eq!(client.mint(&1u128, &user, &3), (1, 3));
+
+    // Mint event is still emitted alongside the receipt
+    let events = env.events().all();
+    let (_, topics, _) = events.get(events.len() - 1).unwrap();
+    assert_eq!(topics, (symbol_short!("mint_log"), 1u128, user.clone(), 3u32, 0u128).into_val(&env));
+
+    assert_eq!(client.mint(&1u128, &user, &2), (4, 2));
+}
+
+#[test]
+fn test_grant_minter_to_collections() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let backend_minter = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    let child1 = env.register(RoleChild, (creator.clone(),));
+    let child2 = env.register(RoleChild, (creator.clone(),));
+    register_child_collection(&env, &factory_address, 1u128, &child1, &creator);
+    register_child_collection(&env, &factory_address, 2u128, &child2, &creator);
+
+    client.grant_minter_to_collections(&Vec::from_array(&env, [1u128, 2u128]), &backend_minter);
+
+    assert!(RoleChildClient::new(&env, &child1).is_minter(&backend_minter));
+    assert!(RoleChildClient::new(&env, &child2).is_minter(&backend_minter));
+}
+
+#[test]
+fn test_grant_minter_after_transfer_collection() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let new_creator = Address::generate(&env);
+    let backend_minter = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    let child = env.register(RoleChild, (creator.clone(),));
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    // Both sides sign: the old creator offers the child admin role, the new one accepts it
+    client.transfer_collection(&1u128, &new_creator);
+    let signers: std::vec::Vec<Address> = env.auths().into_iter().map(|(address, _)| address).collect();
+    assert!(signers.contains(&creator));
+    assert!(signers.contains(&new_creator));
+
+    // The child only accepts set_minter from its admin, which is now the new creator
+    client.grant_minter_to_collections(&Vec::from_array(&env, [1u128]), &backend_minter);
+    assert_eq!(env.auths()[0].0, new_creator);
+    assert!(RoleChildClient::new(&env, &child).is_minter(&backend_minter));
+
+    // Royalties now go to the new creator rather than the original one
+    assert_eq!(RoleChildClient::new(&env, &child).royalty_recipient(), new_creator);
+}
+
+#[test]
+fn test_extend_collection_ttl() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+    assert_eq!(client.get_config().ttl_extension_ledgers, DEFAULT_TTL_EXTENSION);
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    client.extend_collection_ttl(&1u128, &1_000_000);
+    let ttl = env.as_contract(&factory_address, || {
+        env.storage().persistent().get_ttl(&DataKey::Collection(1u128))
+    });
+    assert!(ttl >= 1_000_000);
+
+    // Unknown collections are rejected
+    assert!(client.try_extend_collection_ttl(&2u128, &1_000_000).is_err());
+}
+
+#[test]
+fn test_find_by_name_prefix() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    let names = ["Stellar A", "Lumen Club", "Stellar B"];
+    for (i, name) in names.iter().enumerate() {
+        let collection_id = i as u128 + 1;
+        let child = env.register(MockChild, ());
+        register_child_collection(&env, &factory_address, collection_id, &child, &creator);
+        rename_collection(&env, &factory_address, collection_id, name);
+    }
+
+    let matches = client.find_by_name_prefix(&String::from_str(&env, "Stellar"), &10);
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches.get(0).unwrap().name, String::from_str(&env, "Stellar A"));
+    assert_eq!(matches.get(1).unwrap().name, String::from_str(&env, "Stellar B"));
+
+    // Capped at limit, and no partial-word false positives
+    assert_eq!(client.find_by_name_prefix(&String::from_str(&env, "Stellar"), &1).len(), 1);
+    assert_eq!(client.find_by_name_prefix(&String::from_str(&env, "Stellar AB"), &10).len(), 0);
+}
+
+#[test]
+fn test_active_collection_count() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    for collection_id in 1..=3u128 {
+        let child = env.register(MockChild, ());
+        register_child_collection(&env, &factory_address, collection_id, &child, &creator);
+    }
+    assert_eq!(client.get_active_collection_count(), 3u128);
+
+    client.set_collection_active(&2u128, &false);
+    // Repeating the call does not double count
+    client.set_collection_active(&2u128, &false);
+    assert!(!client.is_collection_active(&2u128));
+    assert_eq!(client.get_active_collection_count(), 2u128);
+    assert_eq!(client.get_total_collections(), 3u128);
+
+    // Deactivated collections cannot mint
+    assert!(!client.can_mint(&2u128, &user, &1));
+    assert!(client.try_mint(&2u128, &user, &1).is_err());
+
+    client.set_collection_active(&2u128, &true);
+    assert_eq!(client.get_active_collection_count(), 3u128);
+    client.mint(&2u128, &user, &1);
+}
+
+#[test]
+fn test_set_verified() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    for collection_id in 1..=3u128 {
+        let child = env.register(MockChild, ());
+        register_child_collection(&env, &factory_address, collection_id, &child, &creator);
+    }
+    assert!(!client.get_collection(&2u128).verified);
+    assert_eq!(client.list_verified(&10).len(), 0);
+
+    client.set_verified(&2u128, &true);
+    client.set_verified(&3u128, &true);
+    assert!(client.get_collection(&2u128).verified);
+    assert_eq!(client.list_verified(&10), Vec::from_array(&env, [2u128, 3u128]));
+    assert_eq!(client.list_verified(&1), Vec::from_array(&env, [2u128]));
+
+    client.set_verified(&3u128, &false);
+    assert_eq!(client.list_verified(&10), Vec::from_array(&env, [2u128]));
+
+    // Only the owner can curate, not even the collection's creator
+    let result = client
+        .mock_auths(&[MockAuth {
+            address: &creator,
+            invoke: &MockAuthInvoke {
+                contract: &factory_address,
+                fn_name: "set_verified",
+                args: (1u128, true).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_set_verified(&1u128, &true);
+    assert!(result.is_err());
+    assert!(!client.get_collection(&1u128).verified);
+}
+
+#[test]
+fn test_list_verified_by_category() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+
+    // 1: verified art, 2: unverified art, 3: verified gaming, 4: verified art
+    let categories = ["art", "art", "gaming", "art"];
+    for (i, category) in categories.iter().enumerate() {
+        let collection_id = i as u128 + 1;
+        let child = env.register(MockChild, ());
+        register_child_collection(&env, &factory_address, collection_id, &child, &creator);
+        set_collection_category(&env, &factory_address, collection_id, category);
+    }
+    client.set_verified(&1u128, &true);
+    client.set_verified(&3u128, &true);
+    client.set_verified(&4u128, &true);
+
+    let art = String::from_str(&env, "art");
+    assert_eq!(client.list_verified_by_category(&art, &10), Vec::from_array(&env, [1u128, 4u128]));
+    assert_eq!(client.list_verified_by_category(&art, &1), Vec::from_array(&env, [1u128]));
+    assert_eq!(
+        client.list_verified_by_category(&String::from_str(&env, "gaming"), &10),
+        Vec::from_array(&env, [3u128])
+    );
+    assert_eq!(client.list_verified_by_category(&String::from_str(&env, "music"), &10).len(), 0);
+}
+
+#[test]
+fn test_creation_enabled_toggle() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+    assert!(client.get_config().creation_enabled);
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    client.set_creation_enabled(&false);
+    assert!(!client.get_config().creation_enabled);
+
+    // New collections are refused
+    let result = client.try_create_collection(
+        &creator,
+        &String::from_str(&env, "Sunset"),
+        &String::from_str(&env, "SUN"),
+        &String::from_str(&env, "https://example.com"),
+        &250,
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
+    );
+    assert!(result.is_err());
+    assert_eq!(client.get_total_collections(), 1u128);
+
+    // Existing collections keep minting
+    client.mint(&1u128, &user, &1);
+    assert_eq!(client.get_collection_mints(&1u128).len(), 1);
+
+    // Re-enabling clears the block
+    let validate = |client: &FactoryRegistryClient| client.try_validate_collection_spec(
+        &simple_spec(&env, "Sunrise", 250),
+    );
+    assert_eq!(validate(&client), Err(Ok(FactoryError::CreationDisabled)));
+    client.set_creation_enabled(&true);
+    assert_eq!(validate(&client), Ok(Ok(())));
+}
+
+#[test]
+fn test_config_change_requires_co_owner_approvals() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let new_fee_wallet = Address::generate(&env);
+    let signer_a = Address::generate(&env);
+    let signer_b = Address::generate(&env);
+    let signer_c = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.set_co_owners(&Vec::from_array(&env, [signer_a.clone(), signer_b.clone(), signer_c.clone()]), &2);
+
+    // The owner's call only proposes the change
+    client.set_config(&500, &new_fee_wallet, &nft_wasm_hash);
+    assert_eq!(client.get_config().fee_bps, 200);
+    assert!(client.get_pending_config().is_some());
+
+    let action = ConfigAction::SetConfig(500, new_fee_wallet.clone(), nft_wasm_hash.clone());
+    assert_eq!(client.get_pending_config().unwrap().action, action);
+
+    // One approval is not enough, and approving twice does not count twice
+    client.approve_config(&signer_a, &action);
+    assert_eq!(client.get_config().fee_bps, 200);
+    assert!(client.try_approve_config(&signer_a, &action).is_err());
+    assert!(client.try_approve_config(&owner, &action).is_err());
+    assert_eq!(client.get_pending_config().unwrap().approvals.len(), 1);
+
+    // The second approval applies it
+    client.approve_config(&signer_b, &action);
+    let config = client.get_config();
+    assert_eq!(config.fee_bps, 500);
+    assert_eq!(config.fee_wallet, new_fee_wallet);
+    assert_eq!(client.get_pending_config(), None);
+    assert!(client.try_approve_config(&signer_c, &action).is_err());
+}
+
+#[test]
+fn test_approval_rejects_swapped_proposal() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let new_fee_wallet = Address::generate(&env);
+    let other_wallet = Address::generate(&env);
+    let signer_a = Address::generate(&env);
+    let signer_b = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.set_co_owners(&Vec::from_array(&env, [signer_a.clone(), signer_b.clone()]), &2);
+
+    client.set_config(&500, &new_fee_wallet, &nft_wasm_hash);
+    let reviewed = ConfigAction::SetConfig(500, new_fee_wallet.clone(), nft_wasm_hash.clone());
+    client.approve_config(&signer_a, &reviewed);
+
+    // The owner swaps the proposal before the last approval
+    client.set_config(&900, &other_wallet, &nft_wasm_hash);
+    let swapped = client.get_pending_config().unwrap();
+    assert_eq!(swapped.action, ConfigAction::SetConfig(900, other_wallet.clone(), nft_wasm_hash.clone()));
+    assert_eq!(swapped.approvals.len(), 0);
+
+    // An approval for the reviewed change does not carry over to the swapped one
+    assert!(client.try_approve_config(&signer_b, &reviewed).is_err());
+    let config = client.get_config();
+    assert_eq!(config.fee_bps, 200);
+    assert_eq!(config.fee_wallet, fee_wallet);
+    assert_eq!(client.get_pending_config().unwrap().approvals.len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Required approvals exceed co-owners")]
+fn test_set_co_owners_rejects_unreachable_threshold() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.set_co_owners(&Vec::from_array(&env, [Address::generate(&env)]), &2);
+}
+
+#[test]
+#[should_panic(expected = "Co-owners require at least one approval")]
+fn test_set_co_owners_rejects_zero_threshold() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.set_co_owners(&Vec::from_array(&env, [Address::generate(&env)]), &0);
+}
+
+#[test]
+fn test_owner_cannot_change_config_alone_with_co_owners() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let signer_a = Address::generate(&env);
+    let signer_b = Address::generate(&env);
+    let fee_token = create_funded_token(&env, &owner, 0);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+    let new_hash = BytesN::from_array(&env, &[2u8; 32]);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.set_co_owners(&Vec::from_array(&env, [signer_a.clone(), signer_b.clone()]), &1);
+
+    // Each fee, WASM and signer setter only proposes while co-owners are set
+    client.set_fee_token(&Some(fee_token.clone()));
+    assert_eq!(client.get_config().fee_token, None);
+    assert_eq!(client.get_pending_config().unwrap().action, ConfigAction::SetFeeToken(Some(fee_token.clone())));
+
+    client.set_fee_free_below(&5);
+    assert_eq!(client.get_config().fee_free_below, 0);
+
+    client.set_royalty_bonus_bps(&1000);
+    assert_eq!(client.get_config().royalty_bonus_bps, 0);
+
+    client.add_approved_wasm(&new_hash);
+    assert!(!client.is_approved_wasm(&new_hash));
+
+    client.remove_approved_wasm(&nft_wasm_hash);
+    assert!(client.is_approved_wasm(&nft_wasm_hash));
+
+    // Dropping the co-owners is itself a change that needs their approval
+    client.set_co_owners(&Vec::new(&env), &0);
+    assert_eq!(client.get_config().required_approvals, 1);
+
+    // A co-owner's approval applies the pending change
+    client.set_fee_token(&Some(fee_token.clone()));
+    client.approve_config(&signer_a, &ConfigAction::SetFeeToken(Some(fee_token.clone())));
+    assert_eq!(client.get_config().fee_token, Some(fee_token));
+    assert_eq!(client.get_pending_config(), None);
+}
+
+#[test]
+fn test_validate_collection_spec() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    // A fresh name with valid royalties passes
+    client.validate_collection_spec(&simple_spec(&env, "Fresh Collection", 250));
+
+    // A name already registered is rejected
+    let result = client.try_validate_collection_spec(&simple_spec(&env, "Child Collection", 250));
+    assert_eq!(result, Err(Ok(FactoryError::NameTaken)));
+
+    // Names longer than the child NFT can compose token names from are rejected
+    let long_name = "A".repeat(MAX_COLLECTION_NAME_LEN as usize + 1);
+    let result = client.try_validate_collection_spec(&simple_spec(&env, &long_name, 250));
+    assert_eq!(result, Err(Ok(FactoryError::NameTooLong)));
+    assert!(client.try_create_collection_with_spec(&creator, &simple_spec(&env, &long_name, 250)).is_err());
+    let max_name = "A".repeat(MAX_COLLECTION_NAME_LEN as usize);
+    client.validate_collection_spec(&simple_spec(&env, &max_name, 250));
+
+    // Royalties above 100% are rejected
+    let result = client.try_validate_collection_spec(&simple_spec(&env, "Fresh Collection", 10001));
+    assert_eq!(result, Err(Ok(FactoryError::RoyaltiesTooHigh)));
+
+    // The rest of the spec is checked as create_collection_with_spec would
+    let mut spec = simple_spec(&env, "Fresh Collection", 250);
+    spec.min_mint_amount = Some(0);
+    assert_eq!(client.try_validate_collection_spec(&spec), Err(Ok(FactoryError::InvalidMinMintAmount)));
+    assert!(client.try_create_collection_with_spec(&creator, &spec).is_err());
+
+    let mut spec = simple_spec(&env, "Fresh Collection", 250);
+    spec.mint_price = 100;
+    assert_eq!(client.try_validate_collection_spec(&spec), Err(Ok(FactoryError::PriceTokenRequired)));
+    assert!(client.try_create_collection_with_spec(&creator, &spec).is_err());
+
+    let mut spec = simple_spec(&env, "Fresh Collection", 250);
+    spec.metadata_version = Some(0);
+    assert_eq!(client.try_validate_collection_spec(&spec), Err(Ok(FactoryError::InvalidMetadataVersion)));
+    assert!(client.try_create_collection_with_spec(&creator, &spec).is_err());
+
+    // Nothing was written
+    assert_eq!(client.get_total_collections(), 1u128);
+    assert!(client.find_by_name(&String::from_str(&env, "Fresh Collection")).is_none());
+}
+
+#[test]
+fn test_create_collection_royalties_enforced() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    let enforced_id = client.create_collection(
+        &creator,
+        &String::from_str(&env, "Enforced"),
+        &String::from_str(&env, "ENF"),
+        &String::from_str(&env, "https://example.com"),
+        &500,
+        &None,
+        &None,
+        &None,
+        &true,
+        &None,
+    );
+    let advisory_id = client.create_collection(
+        &creator,
+        &String::from_str(&env, "Advisory"),
+        &String::from_str(&env, "ADV"),
+        &String::from_str(&env, "https://example.com"),
+        &500,
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
+    );
+
+    assert!(client.get_collection(&enforced_id).royalties_enforced);
+    assert!(!client.get_collection(&advisory_id).royalties_enforced);
+}
+
+#[test]
+fn test_create_collection_with_initial_minter() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let backend_minter = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    let collection_id = client.create_collection(
+        &creator,
+        &String::from_str(&env, "Minted By Backend"),
+        &String::from_str(&env, "MBB"),
+        &String::from_str(&env, "https://example.com"),
+        &500,
+        &None,
+        &None,
+        &None,
+        &false,
+        &Some(backend_minter.clone()),
+    );
+
+    // The designated minter holds the role on the child straight away
+    let child = client.get_collection(&collection_id).contract_id;
+    let has_role: bool = env.invoke_contract(
+        &child,
+        &Symbol::new(&env, "check_role"),
+        Vec::from_array(&env, [backend_minter.into_val(&env), symbol_short!("minter").into_val(&env)]),
+    );
+    assert!(has_role);
+}
+
+#[test]
+fn test_get_collection_wasm_hash() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let first_hash = create_test_nft_wasm_hash(&env);
+    let second_hash = create_test_nft_wasm_hash_variant(&env, 2);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &first_hash);
+
+    let first_id = client.create_collection(
+        &creator,
+        &String::from_str(&env, "First Build"),
+        &String::from_str(&env, "FB1"),
+        &String::from_str(&env, "https://example.com"),
+        &500,
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
+    );
+
+    // Switch the factory to a newly approved build
+    client.add_approved_wasm(&second_hash);
+    client.set_config(&200, &fee_wallet, &second_hash);
+
+    let second_id = client.create_collection(
+        &creator,
+        &String::from_str(&env, "Second Build"),
+        &String::from_str(&env, "FB2"),
+        &String::from_str(&env, "https://example.com"),
+        &500,
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
+    );
+
+    assert_eq!(client.get_collection_wasm_hash(&first_id), first_hash);
+    assert_eq!(client.get_collection_wasm_hash(&second_id), second_hash);
+}
+
+#[test]
+fn test_list_by_category() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    let art = String::from_str(&env, "art");
+    let gaming = String::from_str(&env, "gaming");
+    let names = ["Art 1", "Game 1", "Art 2"];
+    let categories = [&art, &gaming, &art];
+    for (name, category) in names.iter().zip(categories.iter()) {
+        client.create_collection(
+            &creator,
+            &String::from_str(&env, name),
+            &String::from_str(&env, "CAT"),
+            &String::from_str(&env, "https://example.com"),
+            &250,
+            &None,
+            &Some((*category).clone()),
+            &None,
+            &false,
+            &None,
+        );
+    }
+
+    assert_eq!(client.list_by_category(&art), Vec::from_array(&env, [1u128, 3u128]));
+    assert_eq!(client.list_by_category(&gaming), Vec::from_array(&env, [2u128]));
+    assert_eq!(client.list_by_category(&String::from_str(&env, "music")).len(), 0);
+    assert_eq!(client.get_collection(&2u128).category, Some(gaming));
+}
+
+#[test]
+fn test_quote_mint_fee_breakdown() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // Initialize factory with 5% fee
+    client.initialize(&owner, &500, &fee_wallet, &nft_wasm_hash);
+    let fee_token = create_funded_token(&env, &user, 1_000_000);
+    client.set_fee_token(&Some(fee_token));
+
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    // 3 NFTs * 0.1 XLM * 5% = 15,000 stroops, all to the platform
+    let breakdown = client.quote_mint_fee_breakdown(&1u128, &user, &3u32);
+    assert_eq!(breakdown.platform, 150_000u128);
+    assert_eq!(breakdown.creator, 0u128);
+    assert_eq!(breakdown.total, breakdown.platform + breakdown.creator);
+    assert!(!breakdown.exempt);
+
+    // The quote matches what mint records
+    client.mint(&1u128, &user, &3u32);
+    let mints = client.get_collection_mints(&1u128);
+    assert_eq!(mints.get(0).unwrap().fee_paid, breakdown.total);
+
+    // With the fee turned off the mint is exempt
+    client.set_config(&0, &fee_wallet, &nft_wasm_hash);
+    let exempt = client.quote_mint_fee_breakdown(&1u128, &user, &3u32);
+    assert_eq!(exempt.total, 0u128);
+    assert!(exempt.exempt);
+}
+
+#[test]
+#[should_panic(expected = "Royalties below minimum")]
+fn test_create_collection_below_royalty_floor() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // Initialize factory with a 5% royalty floor
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.set_min_royalties_bps(&500);
+
+    client.create_collection(
+        &creator,
+        &String::from_str(&env, "Low Royalties"),
+        &String::from_str(&env, "LOW"),
+        &String::from_str(&env, "https://example.com"),
+        &499,
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
+    );
+}
+
+#[test]
+fn test_create_collection_at_royalty_floor() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // Initialize factory with a 5% royalty floor
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.set_min_royalties_bps(&500);
+
+    let collection_id = client.create_collection(
+        &creator,
+        &String::from_str(&env, "Floor Royalties"),
+        &String::from_str(&env, "FLR"),
+        &String::from_str(&env, "https://example.com"),
+        &500,
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
+    );
+    assert_eq!(client.get_collection(&collection_id).royalties_bps, 500);
+}
+
+#[test]
+fn test_count_collections_by_time() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // Initialize factory
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    // Create collections at controlled ledger times
+    for (id, timestamp) in [(1u128, 1_000u64), (2, 2_000), (3, 3_000), (4, 10_000)] {
+        env.ledger().set_timestamp(timestamp);
+        let child = env.register(MockChild, ());
+        register_child_collection(&env, &factory_address, id, &child, &creator);
+    }
+
+    assert_eq!(client.count_collections_by_time(&1_500u64, &3_000u64), 2u128);
+    assert_eq!(client.count_collections_by_time(&0u64, &u64::MAX), 4u128);
+    assert_eq!(client.count_collections_by_time(&4_000u64, &9_000u64), 0u128);
+}
+
+#[test]
+fn test_get_collections_by_ids() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // Initialize factory
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    let child1 = env.register(MockChild, ());
+    let child2 = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child1, &creator);
+    register_child_collection(&env, &factory_address, 2u128, &child2, &creator);
+
+    // Mix valid and invalid ids
+    let ids = Vec::from_array(&env, [2u128, 99u128, 1u128, 0u128]);
+    let collections = client.get_collections_by_ids(&ids);
+    assert_eq!(collections.len(), 4);
+    assert_eq!(collections.get(0).unwrap().unwrap().contract_id, child2);
+    assert!(collections.get(1).unwrap().is_none());
+    assert_eq!(collections.get(2).unwrap().unwrap().contract_id, child1);
+    assert!(collections.get(3).unwrap().is_none());
+
+    // Oversized batches are rejected
+    let mut too_many = Vec::new(&env);
+    for id in 0..51u128 {
+        too_many.push_back(id);
+    }
+    assert!(client.try_get_collections_by_ids(&too_many).is_err());
+}
+
+#[test]
+fn test_create_collection_with_transfer_lock() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    let spec = CollectionSpec {
+        name: String::from_str(&env, "No Flips"),
+        symbol: String::from_str(&env, "NOFL"),
+        uri_base: String::from_str(&env, "https://example.com/noflips"),
+        royalties_bps: 500,
+        salt: None,
+        category: None,
+        min_mint_amount: None,
+        royalties_enforced: false,
+        mint_price: 0,
+        price_token: None,
+        initial_minter: None,
+        transfer_lock_secs: 86_400,
+        metadata_version: None,
+    };
+    let collection_id = client.create_collection_with_spec(&creator, &spec);
+
+    // The lock is recorded on the collection and threaded into the child
+    let collection = client.get_collection(&collection_id);
+    assert_eq!(collection.transfer_lock_secs, 86_400);
+    let lock_secs: u64 = env.invoke_contract(
+        &collection.contract_id,
+        &Symbol::new(&env, "transfer_lock_secs"),
+        Vec::new(&env),
+    );
+    assert_eq!(lock_secs, 86_400);
+}
+
+#[test]
+fn test_rotate_fee_wallet() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let new_fee_wallet = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.rotate_fee_wallet(&new_fee_wallet);
+
+    // The event carries both the prior and the new wallet
+    let (contract_id, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(contract_id, factory_address);
+    assert_eq!(
+        topics,
+        (Symbol::new(&env, "fee_wallet_rotated"), fee_wallet, new_fee_wallet.clone()).into_val(&env)
+    );
+
+    let config = client.get_config();
+    assert_eq!(config.fee_wallet, new_fee_wallet);
+    assert_eq!(config.fee_bps, 200);
+}
+
+#[test]
+#[should_panic(expected = "Fee wallet change requires co-owner approval")]
+fn test_rotate_fee_wallet_requires_approval_with_co_owners() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let co_owner = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.set_co_owners(&Vec::from_array(&env, [co_owner]), &1);
+    client.rotate_fee_wallet(&Address::generate(&env));
+}
+
+#[test]
+fn test_royalty_bonus_for_verified_collections() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let verified_creator = Address::generate(&env);
+    let other_creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    // 2% of the 1_000_000 base fee per NFT, a quarter of it back to verified creators
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    let fee_token = create_funded_token(&env, &user, 100_000);
+    client.set_fee_token(&Some(fee_token.clone()));
+    client.set_royalty_bonus_bps(&2_500);
+    assert_eq!(client.get_config().royalty_bonus_bps, 2_500);
+
+    let verified_child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &verified_child, &verified_creator);
+    let other_child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 2u128, &other_child, &other_creator);
+    client.set_verified(&1u128, &true);
+
+    let breakdown = client.quote_mint_fee_breakdown(&1u128, &user, &2);
+    assert_eq!(breakdown.creator, 10_000);
+    assert_eq!(breakdown.platform, 30_000);
+    assert_eq!(breakdown.total, 40_000);
+
+    let token_client = token::Client::new(&env, &fee_token);
+
+    // The verified creator receives the bonus out of the platform's share
+    client.mint(&1u128, &user, &2);
+    assert_eq!(token_client.balance(&verified_creator), 10_000);
+    assert_eq!(token_client.balance(&fee_wallet), 30_000);
+
+    // The record carries the full charge with the bonus itemized
+    let record = client.get_collection_mints(&1u128).get(0).unwrap();
+    assert_eq!(record.fee_paid, 40_000);
+    assert_eq!(record.creator_bonus, 10_000);
+
+    // The unverified creator does not
+    client.mint(&2u128, &user, &2);
+    assert_eq!(token_client.balance(&other_creator), 0);
+    assert_eq!(token_client.balance(&fee_wallet), 70_000);
+    assert_eq!(token_client.balance(&user), 20_000);
+    let record = client.get_collection_mints(&2u128).get(0).unwrap();
+    assert_eq!(record.fee_paid, 40_000);
+    assert_eq!(record.creator_bonus, 0);
+}
+
+#[test]
+#[should_panic(expected = "Royalty bonus cannot exceed 10000")]
+fn test_royalty_bonus_bps_too_high() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    client.set_royalty_bonus_bps(&10_001);
+}
+
+#[test]
+fn test_permanently_disable() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+    client.mint(&1u128, &user, &1);
+
+    assert!(!client.is_killed());
+    client.permanently_disable();
+    assert!(client.is_killed());
+
+    // Mints and creation revert, even after the owner re-enables creation
+    assert!(client.try_mint(&1u128, &user, &1).is_err());
+    client.set_creation_enabled(&true);
+    assert!(client.try_create_collection(
+        &creator,
+        &String::from_str(&env, "After Kill"),
+        &String::from_str(&env, "AFTK"),
+        &String::from_str(&env, "https://example.com"),
+        &500,
+        &None,
+        &None,
+        &None,
+        &false,
+        &None,
+    ).is_err());
+    let result = client.try_validate_collection_spec(&simple_spec(&env, "After Kill", 500));
+    assert_eq!(result, Err(Ok(FactoryError::FactoryKilled)));
+
+    // The flag is one-way
+    assert!(client.try_permanently_disable().is_err());
+    assert!(client.is_killed());
+
+    // Reads still work
+    assert_eq!(client.get_collection(&1u128).contract_id, child);
+    assert_eq!(client.get_collection_mints(&1u128).len(), 1);
+    assert_eq!(client.get_config().owner, owner);
+}
+
+#[test]
+fn test_collection_owner_history() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let second_owner = Address::generate(&env);
+    let third_owner = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    env.ledger().set_timestamp(100);
+    let child = env.register(RoleChild, (creator.clone(),));
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    env.ledger().set_timestamp(200);
+    client.transfer_collection(&1u128, &second_owner);
+    env.ledger().set_timestamp(300);
+    client.transfer_collection(&1u128, &third_owner);
+
+    let history = client.get_collection_owner_history(&1u128);
+    assert_eq!(history, Vec::from_array(&env, [
+        (creator.clone(), 100u64),
+        (second_owner.clone(), 200u64),
+        (third_owner.clone(), 300u64),
+    ]));
+
+    // The record and creator indexes follow the transfer
+    assert_eq!(client.get_collection(&1u128).creator, third_owner);
+    assert_eq!(client.list_by_creator(&creator).len(), 0);
+    assert_eq!(client.list_by_creator(&third_owner), Vec::from_array(&env, [1u128]));
+}
+
+#[test]
+fn test_create_collection_with_metadata_version() {
+    let env = Env::default();
+    let (client, _) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    let spec = CollectionSpec {
+        name: String::from_str(&env, "Schema Two"),
+        symbol: String::from_str(&env, "SCH2"),
+        uri_base: String::from_str(&env, "https://example.com/v2"),
+        royalties_bps: 500,
+        salt: None,
+        category: None,
+        min_mint_amount: None,
+        royalties_enforced: false,
+        mint_price: 0,
+        price_token: None,
+        initial_minter: None,
+        transfer_lock_secs: 0,
+        metadata_version: Some(2),
+    };
+    let collection_id = client.create_collection_with_spec(&creator, &spec);
+
+    assert_eq!(client.get_collection(&collection_id).metadata_version, 2);
+}
+
+#[test]
+fn test_set_metadata_version() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+    assert_eq!(client.get_collection(&1u128).metadata_version, 1);
+
+    client.set_metadata_version(&1u128, &2);
+    assert_eq!(client.get_collection(&1u128).metadata_version, 2);
+
+    assert!(client.try_set_metadata_version(&1u128, &0).is_err());
+    assert_eq!(client.get_collection(&1u128).metadata_version, 2);
+}
+
+#[test]
+fn test_collection_at() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+    let children = [env.register(MockChild, ()), env.register(MockChild, ()), env.register(MockChild, ())];
+    for (i, child) in children.iter().enumerate() {
+        register_child_collection(&env, &factory_address, i as u128 + 1, child, &creator);
+    }
+
+    assert_eq!(client.collection_at(&1u128).unwrap().contract_id, children[1]);
+
+    // Deactivating the middle collection closes the gap
+    client.set_collection_active(&2u128, &false);
+    assert_eq!(client.collection_at(&0u128).unwrap().contract_id, children[0]);
+    assert_eq!(client.collection_at(&1u128).unwrap().contract_id, children[2]);
+    assert!(client.collection_at(&2u128).is_none());
+}
+
+#[test]
+fn test_is_collection_healthy() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &200, &fee_wallet, &nft_wasm_hash);
+
+    let healthy = env.register(MetadataChild, (Some(String::from_str(&env, "Healthy")),));
+    register_child_collection(&env, &factory_address, 1u128, &healthy, &creator);
+    assert!(client.is_collection_healthy(&1u128));
+
+    // A child whose getter reverts (e.g. a partially failed deploy) reports unhealthy
+    let broken = env.register(MetadataChild, (None::<String>,));
+    register_child_collection(&env, &factory_address, 2u128, &broken, &creator);
+    assert!(!client.is_collection_healthy(&2u128));
+}
+
+#[test]
+fn test_mint_velocity() {
+    let env = Env::default();
+    let (client, factory_address) = create_factory_contract(&env);
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let user = Address::generate(&env);
+    let nft_wasm_hash = create_test_nft_wasm_hash(&env);
+
+    env.mock_all_auths();
+
+    client.initialize(&owner, &0, &fee_wallet, &nft_wasm_hash);
+    let child = env.register(MockChild, ());
+    register_child_collection(&env, &factory_address, 1u128, &child, &creator);
+
+    for timestamp in [1_000u64, 1_100, 1_130, 1_150] {
+        env.ledger().set_timestamp(timestamp);
+        client.mint(&1u128, &user, &1);
+    }
+
+    // At t=1_160 the last minute covers the mints at 1_100, 1_130 and 1_150
+    env.ledger().set_timestamp(1_160);
+    assert_eq!(client.mint_velocity(&1u128, &60), 3);
+    assert_eq!(client.mint_velocity(&1u128, &30), 2);
+    assert_eq!(client.mint_velocity(&1u128, &1_000), 4);
+
+    // Nothing recent once the window has moved on
+    env.ledger().set_timestamp(2_000);
+    assert_eq!(client.mint_velocity(&1u128, &60), 0);
+    assert_eq!(client.mint_velocity(&2u128, &60), 0);
+}
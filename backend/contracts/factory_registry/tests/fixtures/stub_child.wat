@@ -0,0 +1,81 @@
+;; Minimal stand-in for the NFT child contract, used by the factory tests to
+;; exercise the real deploy path (upload, deploy_v2, constructor, cross-calls).
+;;
+;; It mirrors only the child surface the factory touches:
+;;   __constructor(owner, name, symbol, uri_base, royalties_bps,
+;;                 royalties_enforced, mint_start_id, admin_role)
+;;   set_minter(admin, minter)          -- admin must match and authorize
+;;   check_role(account, role) -> bool  -- role is ignored, only minters exist
+;;   set_transfer_lock(admin, secs)     -- admin must match and authorize
+;;   transfer_lock_secs() -> u64
+;;   contract_admin() -> Address
+;;
+;; Build: wat2wasm --enable-annotations stub_child.wat -o stub_child.wasm
+(module
+  (type $v1 (func (param i64) (result i64)))
+  (type $v2 (func (param i64 i64) (result i64)))
+  (type $v3 (func (param i64 i64 i64) (result i64)))
+  (type $v0 (func (result i64)))
+  (type $v8 (func (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)))
+
+  (import "l" "_" (func $put_contract_data (type $v3)))
+  (import "l" "0" (func $has_contract_data (type $v2)))
+  (import "l" "1" (func $get_contract_data (type $v2)))
+  (import "a" "0" (func $require_auth (type $v1)))
+  (import "x" "0" (func $obj_cmp (type $v2)))
+
+  (memory (export "memory") 1)
+
+  ;; Val constants: Symbol("admin"), Symbol("lock"), true, void, u64 0,
+  ;; and the instance storage type
+  (global $ADMIN i64 (i64.const 166013416206))
+  (global $LOCK i64 (i64.const 3343527950))
+  (global $TRUE i64 (i64.const 1))
+  (global $VOID i64 (i64.const 2))
+  (global $ZERO_U64 i64 (i64.const 6))
+  (global $INSTANCE i64 (i64.const 2))
+
+  ;; Trap unless `admin` is the stored admin and has authorized the call
+  (func $check_admin (param $admin i64)
+    (if (i64.ne
+          (call $obj_cmp
+            (local.get $admin)
+            (call $get_contract_data (global.get $ADMIN) (global.get $INSTANCE)))
+          (i64.const 0))
+      (then unreachable))
+    (drop (call $require_auth (local.get $admin))))
+
+  (func (export "__constructor") (type $v8)
+    (drop (call $put_contract_data
+      (global.get $ADMIN) (local.get 0) (global.get $INSTANCE)))
+    (global.get $VOID))
+
+  (func (export "set_minter") (type $v2)
+    (call $check_admin (local.get 0))
+    (drop (call $put_contract_data
+      (local.get 1) (global.get $TRUE) (global.get $INSTANCE)))
+    (global.get $VOID))
+
+  (func (export "check_role") (type $v2)
+    (call $has_contract_data (local.get 0) (global.get $INSTANCE)))
+
+  (func (export "set_transfer_lock") (type $v2)
+    (call $check_admin (local.get 0))
+    (drop (call $put_contract_data
+      (global.get $LOCK) (local.get 1) (global.get $INSTANCE)))
+    (global.get $VOID))
+
+  (func (export "transfer_lock_secs") (type $v0)
+    (if (result i64)
+      (i64.eq
+        (call $has_contract_data (global.get $LOCK) (global.get $INSTANCE))
+        (global.get $TRUE))
+      (then (call $get_contract_data (global.get $LOCK) (global.get $INSTANCE)))
+      (else (global.get $ZERO_U64))))
+
+  (func (export "contract_admin") (type $v0)
+    (call $get_contract_data (global.get $ADMIN) (global.get $INSTANCE)))
+
+  ;; Env interface version: protocol 22, pre-release 0
+  (@custom "contractenvmetav0" "\00\00\00\00\00\00\00\16\00\00\00\00")
+)
@@ -1,10 +1,10 @@
 #![no_std]
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, Address, Env, String, Symbol
+    contract, contractimpl, contracttype, symbol_short, token, Address, Env, String, Symbol, Vec
 };
 
-use stellar_access::access_control::{set_admin, AccessControl};
+use stellar_access::access_control::{self as access_control, set_admin, AccessControl};
 use stellar_macros::{default_impl, only_admin};
 use stellar_tokens::non_fungible::{Base, NonFungibleToken};
 
@@ -17,14 +17,59 @@ pub struct CollectionMetadata {
     pub royalties_bps: u32,
 }
 
+#[derive(Clone)]
+#[contracttype]
+pub struct TransferEntry {
+    pub from: Address,
+    pub to: Address,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Reservation {
+    pub to: Address,
+    pub amount: u32,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
     CollectionMetadata,
     Initialized,
     NextTokenId,
+    TrackTransfers,
+    TransferHistory(u32),
+    PayoutAddress,
+    RoyaltyRecipient,
+    MintedAt(u32),
+    MaxSupply,
+    Reservation(u32),   // keyed by first reserved token id
+    ReservedCount,      // ids reserved but not yet minted
+    CancelledCount,     // ids released by cancelled reservations
+    MintDelegate,
+    RoyaltiesEnforced,
+    AccountRoles(Address),
+    NameTemplate,
+    MaxMintBatch,
+    MintStartId,         // first id handed out by mint; lower ids are reserved
+    ReservedIdsMinted,   // reserved-range ids minted through mint_reserved
+    Allowlisted(Address),   // presale mints the account has left
+    AllowlistExpiry,
+    TransferLockSecs,
+    DeniedRecipient(Address),
+    BurnedCount,
+    AdminRole,
+    SecondaryFeeBps,
+    SecondaryFeeRecipient,
 }
 
+pub const ADMIN_ROLE: Symbol = symbol_short!("admin");
+pub const MINTER_ROLE: Symbol = symbol_short!("minter");
+pub const MAX_TRANSFER_HISTORY: u32 = 20; // oldest entries are evicted beyond this
+pub const MAX_TOKEN_NAME_BASE: u32 = 64;  // longest name/template usable in token_name
+pub const DEFAULT_MAX_MINT_BATCH: u32 = 50; // keeps a single mint loop within budget
+
 #[contract]
 pub struct NFTContract;
 
@@ -37,6 +82,9 @@ impl NFTContract {
         symbol: String,
         uri_base: String,
         royalties_bps: u32,
+        royalties_enforced: bool,
+        mint_start_id: u32,
+        admin_role: Option<Symbol>,
     ) {
         // Check if already initialized
         if env.storage().instance().has(&DataKey::Initialized) {
@@ -47,9 +95,23 @@ impl NFTContract {
             panic!("Royalties cannot exceed 10000 basis points (100%)");
         }
 
+        if mint_start_id == 0 {
+            panic!("Mint start id must be at least 1");
+        }
+
+        if name.len() > MAX_TOKEN_NAME_BASE {
+            panic!("Collection name too long");
+        }
+
         // Set admin for access control
         set_admin(&env, &owner);
 
+        // A custom admin role administers the minter role in place of the default
+        if let Some(admin_role) = admin_role {
+            env.storage().instance().set(&DataKey::AdminRole, &admin_role);
+            access_control::set_role_admin_no_auth(&env, &MINTER_ROLE, &admin_role);
+        }
+
         // Store collection metadata
         let metadata = CollectionMetadata {
             name: name.clone(),
@@ -60,44 +122,230 @@ impl NFTContract {
 
         env.storage().instance().set(&DataKey::CollectionMetadata, &metadata);
         env.storage().instance().set(&DataKey::Initialized, &true);
-        env.storage().instance().set(&DataKey::NextTokenId, &1u32);
+        env.storage().instance().set(&DataKey::NextTokenId, &mint_start_id);
+        env.storage().instance().set(&DataKey::MintStartId, &mint_start_id);
+        env.storage().instance().set(&DataKey::PayoutAddress, &owner);
+        env.storage().instance().set(&DataKey::RoyaltiesEnforced, &royalties_enforced);
 
         // Set metadata in the NFT base
         Base::set_metadata(&env, uri_base.clone(), name.clone(), symbol.clone());
     }
 
     pub fn mint(env: &Env, caller: Address, to: Address, amount: u32) -> u32 {
-        // Check if caller has minter role
-        let minter_role = symbol_short!("minter");
-        if !<NFTContract as AccessControl>::has_role(env, caller.clone(), minter_role).is_some() {
+        // Check if caller has minter role or is the mint delegate
+        if !is_authorized_minter(env, &caller) {
             panic!("Caller is not a minter");
         }
-        // Get next token ID
-        let next_token_id: u32 = env.storage().instance()
+        mint_next(env, &to, amount)
+    }
+
+    /// Presale mint for allowlisted accounts, open until the allowlist expiry and
+    /// limited to each account's remaining quota
+    pub fn mint_allowlisted(env: &Env, to: Address, amount: u32) -> u32 {
+        to.require_auth();
+
+        let remaining = Self::allowlist_remaining(env, to.clone());
+        if remaining == 0 {
+            panic!("Account not allowlisted");
+        }
+        if amount > remaining {
+            panic!("Allowlist quota exceeded");
+        }
+        if let Some(expiry) = Self::allowlist_expiry(env) {
+            if env.ledger().timestamp() > expiry {
+                panic!("Allowlist expired");
+            }
+        }
+
+        // Spend the quota; an exhausted account drops off the allowlist
+        let key = DataKey::Allowlisted(to.clone());
+        if amount == remaining {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &(remaining - amount));
+        }
+        mint_next(env, &to, amount)
+    }
+
+    /// Allow each account `quota` presale mints (0 removes them from the allowlist)
+    #[only_admin]
+    pub fn set_allowlisted(env: &Env, accounts: Vec<Address>, quota: u32) {
+        for account in accounts.iter() {
+            let key = DataKey::Allowlisted(account);
+            if quota > 0 {
+                env.storage().persistent().set(&key, &quota);
+            } else {
+                env.storage().persistent().remove(&key);
+            }
+        }
+    }
+
+    pub fn is_allowlisted(env: &Env, account: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Allowlisted(account))
+    }
+
+    /// Presale mints `account` has left (0 = not allowlisted)
+    pub fn allowlist_remaining(env: &Env, account: Address) -> u32 {
+        env.storage().persistent()
+            .get(&DataKey::Allowlisted(account))
+            .unwrap_or(0)
+    }
+
+    /// Ledger timestamp after which mint_allowlisted is rejected (None = no expiry)
+    #[only_admin]
+    pub fn set_allowlist_expiry(env: &Env, expiry: Option<u64>) {
+        env.storage().instance().set(&DataKey::AllowlistExpiry, &expiry);
+    }
+
+    pub fn allowlist_expiry(env: &Env) -> Option<u64> {
+        env.storage().instance()
+            .get(&DataKey::AllowlistExpiry)
+            .unwrap_or(None)
+    }
+
+    /// Mint a single id from the reserved range below `mint_start_id`
+    #[only_admin]
+    pub fn mint_reserved(env: &Env, _admin: Address, to: Address, token_id: u32) {
+        if token_id == 0 || token_id >= mint_start_id(env) {
+            panic!("Token id outside reserved range");
+        }
+        if env.storage().persistent().has(&DataKey::MintedAt(token_id)) {
+            panic!("Token id already minted");
+        }
+        if Self::remaining_supply(env) == Some(0) {
+            panic!("Max supply exceeded");
+        }
+
+        mint_range(env, &to, token_id, 1);
+        adjust_count(env, DataKey::ReservedIdsMinted, 1);
+    }
+
+    /// Allocate `amount` token ids for `to` without minting them; returns the first id
+    pub fn reserve(env: &Env, caller: Address, to: Address, amount: u32) -> u32 {
+        require_minter(env, &caller);
+
+        if amount == 0 {
+            panic!("Amount must be greater than zero");
+        }
+        // Finalizing mints the whole reservation in one call
+        if amount > Self::max_mint_batch(env) {
+            panic!("Mint amount exceeds max batch");
+        }
+        if let Some(remaining) = Self::remaining_supply(env) {
+            if amount > remaining {
+                panic!("Max supply exceeded");
+            }
+        }
+
+        let first_id: u32 = env.storage().instance()
             .get(&DataKey::NextTokenId)
             .unwrap_or(1u32);
+        env.storage().instance().set(&DataKey::NextTokenId, &(first_id + amount));
+        env.storage().persistent().set(&DataKey::Reservation(first_id), &Reservation { to, amount });
+        adjust_count(env, DataKey::ReservedCount, amount as i64);
+
+        first_id
+    }
+
+    /// Mint the token ids of a pending reservation
+    pub fn finalize_reservation(env: &Env, caller: Address, first_id: u32) {
+        require_minter(env, &caller);
+
+        let reservation = take_reservation(env, first_id);
+        mint_range(env, &reservation.to, first_id, reservation.amount);
+        adjust_count(env, DataKey::ReservedCount, -(reservation.amount as i64));
+    }
+
+    /// Release the token ids of a pending reservation without minting them
+    pub fn cancel_reservation(env: &Env, caller: Address, first_id: u32) {
+        require_minter(env, &caller);
 
-        // Mint tokens sequentially
-        for i in 0..amount {
-            let token_id = next_token_id + i;
-            Base::mint(env, &to, token_id);
+        let reservation = take_reservation(env, first_id);
+        adjust_count(env, DataKey::ReservedCount, -(reservation.amount as i64));
+        adjust_count(env, DataKey::CancelledCount, reservation.amount as i64);
+    }
+
+    pub fn get_reservation(env: &Env, first_id: u32) -> Option<Reservation> {
+        env.storage().persistent().get(&DataKey::Reservation(first_id))
+    }
+
+    pub fn minted_at(env: &Env, token_id: u32) -> u64 {
+        env.storage().persistent()
+            .get(&DataKey::MintedAt(token_id))
+            .expect("Token does not exist")
+    }
+
+    /// Block (or unblock) an address from receiving tokens by mint or transfer
+    #[only_admin]
+    pub fn set_denied(env: &Env, _admin: Address, addr: Address, denied: bool) {
+        let key = DataKey::DeniedRecipient(addr);
+        if denied {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
         }
+    }
 
-        // Update next token ID
-        env.storage().instance().set(&DataKey::NextTokenId, &(next_token_id + amount));
+    pub fn is_denied(env: &Env, addr: Address) -> bool {
+        env.storage().persistent().has(&DataKey::DeniedRecipient(addr))
+    }
 
-        next_token_id
+    /// Lock each token against transfers for `secs` after it is minted (0 = no lock)
+    #[only_admin]
+    pub fn set_transfer_lock(env: &Env, _admin: Address, secs: u64) {
+        env.storage().instance().set(&DataKey::TransferLockSecs, &secs);
+    }
+
+    pub fn transfer_lock_secs(env: &Env) -> u64 {
+        env.storage().instance()
+            .get(&DataKey::TransferLockSecs)
+            .unwrap_or(0u64)
     }
 
     #[only_admin]
     pub fn set_minter(env: &Env, admin: Address, new_minter: Address) {
-        <NFTContract as AccessControl>::grant_role(env, admin, new_minter, symbol_short!("minter"));
+        <NFTContract as AccessControl>::grant_role(env, admin, new_minter, MINTER_ROLE);
+    }
+
+    #[only_admin]
+    pub fn set_mint_delegate(env: &Env, delegate: Option<Address>) {
+        env.storage().instance().set(&DataKey::MintDelegate, &delegate);
+    }
+
+    pub fn mint_delegate(env: &Env) -> Option<Address> {
+        env.storage().instance()
+            .get(&DataKey::MintDelegate)
+            .unwrap_or(None)
     }
 
     pub fn get_collection_metadata(env: &Env) -> CollectionMetadata {
         env.storage().instance().get(&DataKey::CollectionMetadata).unwrap()
     }
 
+    /// Override the collection name used as the prefix of per-token names (None = collection name)
+    #[only_admin]
+    pub fn set_name_template(env: &Env, template: Option<String>) {
+        if let Some(template) = &template {
+            if template.len() > MAX_TOKEN_NAME_BASE {
+                panic!("Name template too long");
+            }
+        }
+        env.storage().instance().set(&DataKey::NameTemplate, &template);
+    }
+
+    pub fn name_template(env: &Env) -> Option<String> {
+        env.storage().instance()
+            .get(&DataKey::NameTemplate)
+            .unwrap_or(None)
+    }
+
+    /// Display name for a single token, e.g. "Stellar Wizards #42"
+    pub fn token_name(env: &Env, token_id: u32) -> String {
+        let base = Self::name_template(env)
+            .unwrap_or_else(|| Self::get_collection_metadata(env).name);
+        compose_token_name(env, &base, token_id)
+    }
+
     pub fn get_royalties(env: &Env) -> u32 {
         let metadata: CollectionMetadata = env.storage().instance()
             .get(&DataKey::CollectionMetadata)
@@ -105,29 +353,229 @@ impl NFTContract {
         metadata.royalties_bps
     }
 
+    #[only_admin]
+    pub fn set_payout_address(env: &Env, payout_address: Address) {
+        env.storage().instance().set(&DataKey::PayoutAddress, &payout_address);
+    }
+
+    pub fn payout_address(env: &Env) -> Address {
+        env.storage().instance()
+            .get(&DataKey::PayoutAddress)
+            .unwrap_or_else(|| Self::contract_admin(env))
+    }
+
+    #[only_admin]
+    pub fn set_royalty_recipient(env: &Env, recipient: Address) {
+        env.storage().instance().set(&DataKey::RoyaltyRecipient, &recipient);
+    }
+
+    /// Where royalties are paid: the explicit royalty recipient, else the payout address
+    pub fn royalty_recipient(env: &Env) -> Address {
+        env.storage().instance()
+            .get(&DataKey::RoyaltyRecipient)
+            .unwrap_or_else(|| Self::payout_address(env))
+    }
+
+    /// Royalty owed on a sale: (recipient, amount)
+    pub fn royalty_info(env: &Env, _token_id: u32, sale_price: i128) -> (Address, i128) {
+        let royalty_amount = sale_price * Self::get_royalties(env) as i128 / 10000;
+        (Self::royalty_recipient(env), royalty_amount)
+    }
+
+    pub fn royalties_enforced(env: &Env) -> bool {
+        env.storage().instance().get(&DataKey::RoyaltiesEnforced).unwrap_or(false)
+    }
+
+    /// Royalty-enforcing sale: the buyer pays `price` in `payment_token`, the royalty goes to
+    /// the royalty recipient, the secondary fee to the platform and the rest to the seller, then
+    /// the token moves to the buyer.
+    /// Only available when the collection enforces royalties; otherwise sales use plain transfers.
+    pub fn sell(env: &Env, seller: Address, buyer: Address, token_id: u32, price: i128, payment_token: Address) {
+        if !Self::royalties_enforced(env) {
+            panic!("Royalties are not enforced for this collection");
+        }
+        if price <= 0 {
+            panic!("Price must be greater than zero");
+        }
+        check_recipient(env, &buyer);
+        check_transfer_lock(env, token_id);
+        buyer.require_auth();
+
+        let (recipient, royalty) = Self::royalty_info(env, token_id, price);
+        let payment = token::Client::new(env, &payment_token);
+        if royalty > 0 {
+            payment.transfer(&buyer, &recipient, &royalty);
+        }
+
+        // The platform's cut comes after the royalty and never exceeds what is left
+        let (fee_bps, fee_recipient) = Self::secondary_fee(env);
+        let secondary_fee = match &fee_recipient {
+            Some(fee_recipient) if fee_bps > 0 => {
+                let fee = ((price * fee_bps as i128) / 10000).min(price - royalty);
+                if fee > 0 {
+                    payment.transfer(&buyer, fee_recipient, &fee);
+                }
+                fee
+            }
+            _ => 0,
+        };
+        payment.transfer(&buyer, &seller, &(price - royalty - secondary_fee));
+
+        Base::transfer(env, &seller, &buyer, token_id);
+        record_transfer(env, &seller, &buyer, token_id);
+    }
+
+    /// Platform fee taken on `sell` in addition to royalties (0 / None = off)
+    #[only_admin]
+    pub fn set_secondary_fee(env: &Env, fee_bps: u32, recipient: Option<Address>) {
+        if fee_bps > 10000 {
+            panic!("Secondary fee cannot exceed 10000 basis points (100%)");
+        }
+        if fee_bps > 0 && recipient.is_none() {
+            panic!("Secondary fee recipient required");
+        }
+        env.storage().instance().set(&DataKey::SecondaryFeeBps, &fee_bps);
+        env.storage().instance().set(&DataKey::SecondaryFeeRecipient, &recipient);
+    }
+
+    pub fn secondary_fee(env: &Env) -> (u32, Option<Address>) {
+        let fee_bps: u32 = env.storage().instance().get(&DataKey::SecondaryFeeBps).unwrap_or(0);
+        let recipient: Option<Address> = env.storage().instance()
+            .get(&DataKey::SecondaryFeeRecipient)
+            .unwrap_or(None);
+        (fee_bps, recipient)
+    }
+
     pub fn check_role(env: &Env, account: Address, role: Symbol) -> bool {
         <NFTContract as AccessControl>::has_role(env, account, role).is_some()
     }
 
-    #[only_admin]
-    pub fn assign_role(env: &Env, admin: Address, account: Address, role: Symbol) {
-        <NFTContract as AccessControl>::grant_role(env, admin, account, role);
+    /// Grant `role`; callable by the admin or a holder of the role's admin role
+    pub fn assign_role(env: &Env, caller: Address, account: Address, role: Symbol) {
+        <NFTContract as AccessControl>::grant_role(env, caller, account, role);
     }
 
     #[only_admin]
-    pub fn remove_role(env: &Env, admin: Address, account: Address, role: Symbol) {
-        <NFTContract as AccessControl>::revoke_role(env, admin, account, role);
+    pub fn grant_roles(env: &Env, admin: Address, accounts: Vec<Address>, role: Symbol) {
+        for account in accounts.iter() {
+            <NFTContract as AccessControl>::grant_role(env, admin.clone(), account, role.clone());
+        }
+    }
+
+    /// Revoke `role`; callable by the admin or a holder of the role's admin role
+    pub fn remove_role(env: &Env, caller: Address, account: Address, role: Symbol) {
+        <NFTContract as AccessControl>::revoke_role(env, caller, account, role);
+    }
+
+    /// Every role currently held by `account`
+    pub fn roles_of(env: &Env, account: Address) -> Vec<Symbol> {
+        env.storage().persistent()
+            .get(&DataKey::AccountRoles(account))
+            .unwrap_or(Vec::new(env))
+    }
+
+    /// Role that administers other roles (ADMIN_ROLE unless overridden at construction)
+    pub fn admin_role(env: &Env) -> Symbol {
+        env.storage().instance()
+            .get(&DataKey::AdminRole)
+            .unwrap_or(ADMIN_ROLE)
     }
 
     pub fn contract_admin(env: &Env) -> Address {
         <NFTContract as AccessControl>::get_admin(env).expect("Admin not set")
     }
 
-    pub fn total_supply(env: &Env) -> u32 {
-        let next_token_id: u32 = env.storage().instance()
+    /// Number of top-level admins; holders of the admin role administer roles, not the contract
+    pub fn admin_count(env: &Env) -> u32 {
+        match access_control::get_admin(env) {
+            Some(_) => 1,
+            None => 0,
+        }
+    }
+
+    pub fn is_last_admin(env: &Env, account: Address) -> bool {
+        access_control::get_admin(env) == Some(account)
+    }
+
+    #[only_admin]
+    pub fn set_track_transfers(env: &Env, enabled: bool) {
+        env.storage().instance().set(&DataKey::TrackTransfers, &enabled);
+    }
+
+    pub fn get_transfer_history(env: &Env, token_id: u32) -> Vec<TransferEntry> {
+        env.storage().persistent()
+            .get(&DataKey::TransferHistory(token_id))
+            .unwrap_or(Vec::new(env))
+    }
+
+    pub fn next_token_id(env: &Env) -> u32 {
+        env.storage().instance()
             .get(&DataKey::NextTokenId)
-            .unwrap_or(1u32);
-        next_token_id - 1
+            .unwrap_or(1u32)
+    }
+
+    #[only_admin]
+    pub fn set_max_supply(env: &Env, max_supply: Option<u32>) {
+        if let Some(max_supply) = max_supply {
+            if max_supply < allocated_supply(env) {
+                panic!("Max supply below current supply");
+            }
+        }
+        env.storage().instance().set(&DataKey::MaxSupply, &max_supply);
+    }
+
+    #[only_admin]
+    pub fn set_max_mint_batch(env: &Env, max_mint_batch: u32) {
+        if max_mint_batch == 0 {
+            panic!("Max mint batch must be at least 1");
+        }
+        env.storage().instance().set(&DataKey::MaxMintBatch, &max_mint_batch);
+    }
+
+    /// Largest `amount` accepted by a single mint or reserve call
+    pub fn max_mint_batch(env: &Env) -> u32 {
+        env.storage().instance()
+            .get(&DataKey::MaxMintBatch)
+            .unwrap_or(DEFAULT_MAX_MINT_BATCH)
+    }
+
+    /// Tokens left to mint under the supply cap, counting reservations (None = uncapped)
+    pub fn remaining_supply(env: &Env) -> Option<u32> {
+        let max_supply: Option<u32> = env.storage().instance()
+            .get(&DataKey::MaxSupply)
+            .unwrap_or(None);
+        max_supply.map(|max_supply| max_supply - allocated_supply(env))
+    }
+
+    /// Number of tokens in circulation (excludes pending reservations and burned tokens)
+    pub fn total_supply(env: &Env) -> u32 {
+        let reserved: u32 = env.storage().instance().get(&DataKey::ReservedCount).unwrap_or(0);
+        allocated_supply(env) - reserved - Self::burned_count(env)
+    }
+
+    /// Burn several of `from`'s tokens at once; reverts entirely if any isn't theirs
+    pub fn batch_burn(env: &Env, from: Address, token_ids: Vec<u32>) {
+        from.require_auth();
+
+        if token_ids.len() > Self::max_mint_batch(env) {
+            panic!("Burn amount exceeds max batch");
+        }
+
+        for token_id in token_ids.iter() {
+            if Base::owner_of(env, token_id) != from {
+                panic!("Token not owned by sender");
+            }
+            // update skips the per-token auth that Base::burn would repeat
+            Base::update(env, Some(&from), None, token_id);
+            env.events().publish((symbol_short!("burn"), from.clone()), token_id);
+        }
+
+        adjust_count(env, DataKey::BurnedCount, token_ids.len() as i64);
+    }
+
+    /// Tokens destroyed through batch_burn; they still count against the supply cap
+    pub fn burned_count(env: &Env) -> u32 {
+        env.storage().instance().get(&DataKey::BurnedCount).unwrap_or(0)
     }
 }
 
@@ -137,6 +585,20 @@ impl NFTContract {
 impl NonFungibleToken for NFTContract {
     type ContractType = Base;
 
+    fn transfer(env: &Env, from: Address, to: Address, token_id: u32) {
+        check_recipient(env, &to);
+        check_transfer_lock(env, token_id);
+        Base::transfer(env, &from, &to, token_id);
+        record_transfer(env, &from, &to, token_id);
+    }
+
+    fn transfer_from(env: &Env, spender: Address, from: Address, to: Address, token_id: u32) {
+        check_recipient(env, &to);
+        check_transfer_lock(env, token_id);
+        Base::transfer_from(env, &spender, &from, &to, token_id);
+        record_transfer(env, &from, &to, token_id);
+    }
+
     fn token_uri(env: &Env, _token_id: u32) -> String {
         let metadata: CollectionMetadata = env.storage().instance()
             .get(&DataKey::CollectionMetadata)
@@ -148,7 +610,202 @@ impl NonFungibleToken for NFTContract {
     }
 }
 
-// Implement AccessControl trait
+fn require_minter(env: &Env, caller: &Address) {
+    caller.require_auth();
+    if !is_authorized_minter(env, caller) {
+        panic!("Caller is not a minter");
+    }
+}
+
+// Minter-role holders and the configured delegate may mint; the delegate must sign
+fn is_authorized_minter(env: &Env, caller: &Address) -> bool {
+    if <NFTContract as AccessControl>::has_role(env, caller.clone(), MINTER_ROLE).is_some() {
+        return true;
+    }
+    if NFTContract::mint_delegate(env) == Some(caller.clone()) {
+        caller.require_auth();
+        return true;
+    }
+    false
+}
+
+// Mint `amount` tokens at the next ids after the batch and supply checks; returns the first id
+fn mint_next(env: &Env, to: &Address, amount: u32) -> u32 {
+    if amount > NFTContract::max_mint_batch(env) {
+        panic!("Mint amount exceeds max batch");
+    }
+    // Get next token ID
+    let next_token_id: u32 = env.storage().instance()
+        .get(&DataKey::NextTokenId)
+        .unwrap_or(1u32);
+
+    if let Some(remaining) = NFTContract::remaining_supply(env) {
+        if amount > remaining {
+            panic!("Max supply exceeded");
+        }
+    }
+
+    // Mint tokens sequentially
+    mint_range(env, to, next_token_id, amount);
+
+    // Update next token ID
+    env.storage().instance().set(&DataKey::NextTokenId, &(next_token_id + amount));
+
+    next_token_id
+}
+
+// Mint `amount` sequential ids starting at `first_id`, stamping each with the mint time
+fn mint_range(env: &Env, to: &Address, first_id: u32, amount: u32) {
+    check_recipient(env, to);
+    let minted_at = env.ledger().timestamp();
+    for i in 0..amount {
+        let token_id = first_id + i;
+        Base::mint(env, to, token_id);
+        env.storage().persistent().set(&DataKey::MintedAt(token_id), &minted_at);
+    }
+}
+
+fn check_recipient(env: &Env, to: &Address) {
+    if NFTContract::is_denied(env, to.clone()) {
+        panic!("Recipient is denied");
+    }
+}
+
+fn check_transfer_lock(env: &Env, token_id: u32) {
+    let lock_secs = NFTContract::transfer_lock_secs(env);
+    if lock_secs == 0 {
+        return;
+    }
+    let minted_at: u64 = env.storage().persistent()
+        .get(&DataKey::MintedAt(token_id))
+        .unwrap_or(0u64);
+    if env.ledger().timestamp() < minted_at.saturating_add(lock_secs) {
+        panic!("Token is transfer locked");
+    }
+}
+
+fn take_reservation(env: &Env, first_id: u32) -> Reservation {
+    let reservation: Reservation = env.storage().persistent()
+        .get(&DataKey::Reservation(first_id))
+        .expect("Reservation not found");
+    env.storage().persistent().remove(&DataKey::Reservation(first_id));
+    reservation
+}
+
+fn adjust_count(env: &Env, key: DataKey, delta: i64) {
+    let count: u32 = env.storage().instance().get(&key).unwrap_or(0);
+    env.storage().instance().set(&key, &((count as i64 + delta) as u32));
+}
+
+// Ids minted or reserved: every id allocated from mint_start_id except those released by
+// cancellations, plus reserved-range ids minted individually
+fn allocated_supply(env: &Env) -> u32 {
+    let next_token_id: u32 = env.storage().instance()
+        .get(&DataKey::NextTokenId)
+        .unwrap_or(1u32);
+    let cancelled: u32 = env.storage().instance().get(&DataKey::CancelledCount).unwrap_or(0);
+    let reserved_minted: u32 = env.storage().instance().get(&DataKey::ReservedIdsMinted).unwrap_or(0);
+    next_token_id - mint_start_id(env) - cancelled + reserved_minted
+}
+
+fn mint_start_id(env: &Env) -> u32 {
+    env.storage().instance().get(&DataKey::MintStartId).unwrap_or(1u32)
+}
+
+// Append a provenance entry for a token when transfer tracking is enabled
+fn record_transfer(env: &Env, from: &Address, to: &Address, token_id: u32) {
+    let track: bool = env.storage().instance().get(&DataKey::TrackTransfers).unwrap_or(false);
+    if !track {
+        return;
+    }
+
+    let mut history: Vec<TransferEntry> = env.storage().persistent()
+        .get(&DataKey::TransferHistory(token_id))
+        .unwrap_or(Vec::new(env));
+    if history.len() >= MAX_TRANSFER_HISTORY {
+        history.pop_front();
+    }
+    history.push_back(TransferEntry {
+        from: from.clone(),
+        to: to.clone(),
+        timestamp: env.ledger().timestamp(),
+    });
+    env.storage().persistent().set(&DataKey::TransferHistory(token_id), &history);
+}
+
+// Implement AccessControl trait, refusing to drop the final admin
 #[default_impl]
 #[contractimpl]
-impl AccessControl for NFTContract {}
\ No newline at end of file
+impl AccessControl for NFTContract {
+    fn grant_role(e: &Env, caller: Address, account: Address, role: Symbol) {
+        access_control::grant_role(e, &caller, &account, &role);
+        track_role(e, &account, &role, true);
+    }
+
+    fn revoke_role(e: &Env, caller: Address, account: Address, role: Symbol) {
+        access_control::revoke_role(e, &caller, &account, &role);
+        track_role(e, &account, &role, false);
+    }
+
+    fn renounce_role(e: &Env, caller: Address, role: Symbol) {
+        access_control::renounce_role(e, &caller, &role);
+        track_role(e, &caller, &role, false);
+    }
+
+    // The admin is only handed over through transfer_admin_role/accept_admin_transfer
+    fn renounce_admin(_e: &Env) {
+        panic!("Cannot remove the last admin");
+    }
+}
+
+// "<base> #<id>" built in a fixed buffer, since there is no heap formatting in no_std
+fn compose_token_name(env: &Env, base: &String, token_id: u32) -> String {
+    if base.len() > MAX_TOKEN_NAME_BASE {
+        panic!("Name too long for token_name");
+    }
+
+    // Base, " #", and at most 10 digits for a u32
+    let mut buf = [0u8; MAX_TOKEN_NAME_BASE as usize + 12];
+    let base_len = base.len() as usize;
+    base.copy_into_slice(&mut buf[..base_len]);
+    buf[base_len] = b' ';
+    buf[base_len + 1] = b'#';
+
+    let mut digits = [0u8; 10];
+    let mut digit_count = 0;
+    let mut remaining = token_id;
+    loop {
+        digits[digit_count] = b'0' + (remaining % 10) as u8;
+        digit_count += 1;
+        remaining /= 10;
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    let mut len = base_len + 2;
+    for digit in digits[..digit_count].iter().rev() {
+        buf[len] = *digit;
+        len += 1;
+    }
+
+    String::from_bytes(env, &buf[..len])
+}
+
+// Keep the per-account role list behind roles_of in step with grants and revocations
+fn track_role(e: &Env, account: &Address, role: &Symbol, held: bool) {
+    let key = DataKey::AccountRoles(account.clone());
+    let mut roles: Vec<Symbol> = e.storage().persistent().get(&key).unwrap_or(Vec::new(e));
+    match (held, roles.first_index_of(role)) {
+        (true, None) => roles.push_back(role.clone()),
+        (false, Some(index)) => {
+            roles.remove(index);
+        }
+        _ => return,
+    }
+    if roles.is_empty() {
+        e.storage().persistent().remove(&key);
+    } else {
+        e.storage().persistent().set(&key, &roles);
+    }
+}
@@ -1,32 +1,58 @@
 #![cfg(test)]
 
 use soroban_sdk::{
-    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
-    Address, Env, InvokeError, String, Symbol, symbol_short
+    testutils::{Address as _, Events, Ledger},
+    token, Address, Env, IntoVal, String, Symbol, symbol_short, vec
 };
 
-use stellar_wizard_nft::{NFTContract, NFTContractClient, MINTER_ROLE};
+use stellar_wizard_nft::{NFTContract, NFTContractClient, ADMIN_ROLE, MAX_TOKEN_NAME_BASE, MINTER_ROLE};
 
-fn create_nft_contract<'a>(env: &Env) -> (NFTContractClient<'a>, Address) {
-    let contract_address = env.register_contract(None, NFTContract);
-    let client = NFTContractClient::new(env, &contract_address);
-    (client, contract_address)
+fn create_initialized_nft_contract<'a>(env: &Env, owner: &Address) -> NFTContractClient<'a> {
+    create_nft_contract_with_enforcement(env, owner, false)
+}
+
+fn create_nft_contract_with_enforcement<'a>(env: &Env, owner: &Address, royalties_enforced: bool) -> NFTContractClient<'a> {
+    create_nft_contract_with_options(env, owner, royalties_enforced, 1)
+}
+
+fn create_nft_contract_with_options<'a>(
+    env: &Env,
+    owner: &Address,
+    royalties_enforced: bool,
+    mint_start_id: u32,
+) -> NFTContractClient<'a> {
+    let contract_address = env.register(
+        NFTContract,
+        (
+            owner.clone(),
+            String::from_str(env, "Test NFTs"),
+            String::from_str(env, "TEST"),
+            String::from_str(env, "https://example.com"),
+            100u32,
+            royalties_enforced,
+            mint_start_id,
+            None::<Symbol>,
+        ),
+    );
+    NFTContractClient::new(env, &contract_address)
 }
 
 #[test]
 fn test_init_contract() {
     let env = Env::default();
-    let (client, _) = create_nft_contract(&env);
+    env.mock_all_auths();
 
     let owner = Address::generate(&env);
     let name = String::from_str(&env, "Stellar Wizards");
     let symbol = String::from_str(&env, "SWIZ");
     let uri_base = String::from_str(&env, "https://api.stellarwizards.com/metadata");
-    let royalties_bps = 250; // 2.5%
+    let royalties_bps = 250u32; // 2.5%
 
-    env.mock_all_auths();
-
-    client.init(&owner, &name, &symbol, &uri_base, &royalties_bps);
+    let contract_address = env.register(
+        NFTContract,
+        (owner.clone(), name.clone(), symbol.clone(), uri_base.clone(), royalties_bps, false, 1u32, None::<Symbol>),
+    );
+    let client = NFTContractClient::new(&env, &contract_address);
 
     // Verify collection metadata
     let metadata = client.get_collection_metadata();
@@ -40,43 +66,34 @@ fn test_init_contract() {
     assert_eq!(client.symbol(), symbol);
     assert_eq!(client.total_supply(), 0);
 
-    // Verify owner has default admin role
-    assert!(client.has_role(&client.default_admin_role(), &owner));
+    // Verify owner is the contract admin
+    assert_eq!(client.get_admin(), Some(owner.clone()));
+    assert_eq!(client.contract_admin(), owner);
 }
 
 #[test]
 fn test_set_minter_and_mint() {
     let env = Env::default();
-    let (client, _) = create_nft_contract(&env);
+    env.mock_all_auths();
 
     let owner = Address::generate(&env);
     let minter = Address::generate(&env);
     let user = Address::generate(&env);
-
-    env.mock_all_auths();
-
-    // Initialize contract
-    client.init(
-        &owner,
-        &String::from_str(&env, "Test NFTs"),
-        &String::from_str(&env, "TEST"),
-        &String::from_str(&env, "https://example.com"),
-        &100,
-    );
+    let client = create_initialized_nft_contract(&env, &owner);
 
     // Set minter
-    client.set_minter(&minter);
+    client.set_minter(&owner, &minter);
 
     // Verify minter has MINTER_ROLE
-    assert!(client.has_role(&MINTER_ROLE, &minter));
+    assert!(client.check_role(&minter, &MINTER_ROLE));
 
     // Mint tokens
-    let first_token_id = client.mint(&user, &3);
+    let first_token_id = client.mint(&minter, &user, &3);
     assert_eq!(first_token_id, 1);
 
     // Verify minting results
     assert_eq!(client.total_supply(), 3);
-    assert_eq!(client.balance_of(&user), 3);
+    assert_eq!(client.balance(&user), 3);
     assert_eq!(client.owner_of(&1), user);
     assert_eq!(client.owner_of(&2), user);
     assert_eq!(client.owner_of(&3), user);
@@ -85,200 +102,151 @@ fn test_set_minter_and_mint() {
 #[test]
 fn test_mint_without_minter_role_fails() {
     let env = Env::default();
-    let (client, _) = create_nft_contract(&env);
+    env.mock_all_auths();
 
     let owner = Address::generate(&env);
     let non_minter = Address::generate(&env);
     let user = Address::generate(&env);
-
-    env.mock_all_auths();
-
-    // Initialize contract
-    client.init(
-        &owner,
-        &String::from_str(&env, "Test NFTs"),
-        &String::from_str(&env, "TEST"),
-        &String::from_str(&env, "https://example.com"),
-        &100,
-    );
+    let client = create_initialized_nft_contract(&env, &owner);
 
     // Try to mint without minter role - should fail
-    let result = client.try_mint(&user, &1);
+    let result = client.try_mint(&non_minter, &user, &1);
     assert!(result.is_err());
+    assert_eq!(client.total_supply(), 0);
 }
 
 #[test]
 fn test_token_uri() {
     let env = Env::default();
-    let (client, _) = create_nft_contract(&env);
+    env.mock_all_auths();
 
     let owner = Address::generate(&env);
     let minter = Address::generate(&env);
     let user = Address::generate(&env);
-
-    env.mock_all_auths();
-
-    let uri_base = String::from_str(&env, "https://api.example.com/metadata");
-
-    // Initialize contract
-    client.init(
-        &owner,
-        &String::from_str(&env, "Test NFTs"),
-        &String::from_str(&env, "TEST"),
-        &uri_base,
-        &100,
-    );
+    let client = create_initialized_nft_contract(&env, &owner);
 
     // Set minter and mint a token
-    client.set_minter(&minter);
-    client.mint(&user, &1);
+    client.set_minter(&owner, &minter);
+    client.mint(&minter, &user, &1);
 
-    // Test token URI generation
-    let expected_uri = String::from_str(&env, "https://api.example.com/metadata/1.json");
-    assert_eq!(client.token_uri(&1), expected_uri);
-
-    let expected_uri_2 = String::from_str(&env, "https://api.example.com/metadata/42.json");
-    assert_eq!(client.token_uri(&42), expected_uri_2);
+    // Every token resolves to the collection's base URI
+    let uri_base = String::from_str(&env, "https://example.com");
+    assert_eq!(client.token_uri(&1), uri_base);
+    assert_eq!(client.token_uri(&42), uri_base);
 }
 
 #[test]
 fn test_transfer_functionality() {
     let env = Env::default();
-    let (client, _) = create_nft_contract(&env);
+    env.mock_all_auths();
 
     let owner = Address::generate(&env);
     let minter = Address::generate(&env);
     let user1 = Address::generate(&env);
     let user2 = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
 
-    env.mock_all_auths();
-
-    // Initialize and setup
-    client.init(
-        &owner,
-        &String::from_str(&env, "Test NFTs"),
-        &String::from_str(&env, "TEST"),
-        &String::from_str(&env, "https://example.com"),
-        &100,
-    );
-
-    client.set_minter(&minter);
-    client.mint(&user1, &1);
+    client.set_minter(&owner, &minter);
+    client.mint(&minter, &user1, &1);
 
     // Initial state
     assert_eq!(client.owner_of(&1), user1);
-    assert_eq!(client.balance_of(&user1), 1);
-    assert_eq!(client.balance_of(&user2), 0);
+    assert_eq!(client.balance(&user1), 1);
+    assert_eq!(client.balance(&user2), 0);
 
     // Transfer token
-    client.transfer_from(&user1, &user2, &1);
+    client.transfer(&user1, &user2, &1);
 
     // Verify transfer
     assert_eq!(client.owner_of(&1), user2);
-    assert_eq!(client.balance_of(&user1), 0);
-    assert_eq!(client.balance_of(&user2), 1);
+    assert_eq!(client.balance(&user1), 0);
+    assert_eq!(client.balance(&user2), 1);
 }
 
 #[test]
 fn test_approval_functionality() {
     let env = Env::default();
-    let (client, _) = create_nft_contract(&env);
+    env.mock_all_auths();
 
     let owner = Address::generate(&env);
     let minter = Address::generate(&env);
     let user1 = Address::generate(&env);
     let user2 = Address::generate(&env);
     let approved = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
 
-    env.mock_all_auths();
-
-    // Initialize and setup
-    client.init(
-        &owner,
-        &String::from_str(&env, "Test NFTs"),
-        &String::from_str(&env, "TEST"),
-        &String::from_str(&env, "https://example.com"),
-        &100,
-    );
+    client.set_minter(&owner, &minter);
+    client.mint(&minter, &user1, &1);
 
-    client.set_minter(&minter);
-    client.mint(&user1, &1);
-
-    // Approve user2 for token 1
-    client.approve(&approved, &1);
+    // Approve a spender for token 1
+    let live_until = env.ledger().sequence() + 1000;
+    client.approve(&user1, &approved, &1, &live_until);
 
     // Verify approval
     assert_eq!(client.get_approved(&1), Some(approved.clone()));
 
     // Test approval for all
-    client.set_approval_for_all(&user2, &true);
+    client.approve_for_all(&user1, &user2, &live_until);
     assert!(client.is_approved_for_all(&user1, &user2));
 
     // Revoke approval for all
-    client.set_approval_for_all(&user2, &false);
+    client.approve_for_all(&user1, &user2, &0);
     assert!(!client.is_approved_for_all(&user1, &user2));
 }
 
 #[test]
 fn test_access_control_functions() {
     let env = Env::default();
-    let (client, _) = create_nft_contract(&env);
+    env.mock_all_auths();
 
     let owner = Address::generate(&env);
     let user1 = Address::generate(&env);
     let user2 = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
 
-    env.mock_all_auths();
-
-    // Initialize contract
-    client.init(
-        &owner,
-        &String::from_str(&env, "Test NFTs"),
-        &String::from_str(&env, "TEST"),
-        &String::from_str(&env, "https://example.com"),
-        &100,
-    );
-
-    let admin_role = client.default_admin_role();
-
-    // Owner should have admin role
-    assert!(client.has_role(&admin_role, &owner));
-    assert!(!client.has_role(&admin_role, &user1));
+    // Owner is the top-level admin
+    assert_eq!(client.contract_admin(), owner);
+    assert!(!client.check_role(&user1, &ADMIN_ROLE));
 
     // Grant admin role to user1
-    client.grant_role(&admin_role, &user1);
-    assert!(client.has_role(&admin_role, &user1));
+    client.assign_role(&owner, &user1, &ADMIN_ROLE);
+    assert!(client.check_role(&user1, &ADMIN_ROLE));
 
     // Grant minter role to user2
-    client.grant_role(&MINTER_ROLE, &user2);
-    assert!(client.has_role(&MINTER_ROLE, &user2));
+    client.assign_role(&owner, &user2, &MINTER_ROLE);
+    assert!(client.check_role(&user2, &MINTER_ROLE));
 
     // Revoke minter role from user2
-    client.revoke_role(&MINTER_ROLE, &user2);
-    assert!(!client.has_role(&MINTER_ROLE, &user2));
+    client.remove_role(&owner, &user2, &MINTER_ROLE);
+    assert!(!client.check_role(&user2, &MINTER_ROLE));
 
-    // Test role admin
-    assert_eq!(client.get_role_admin(&MINTER_ROLE), admin_role);
+    // Without a custom admin role only the top-level admin manages minters
+    assert_eq!(client.get_role_admin(&MINTER_ROLE), None);
 }
 
 #[test]
 fn test_royalties() {
     let env = Env::default();
-    let (client, _) = create_nft_contract(&env);
-
-    let owner = Address::generate(&env);
-    let royalties_bps = 750; // 7.5%
-
     env.mock_all_auths();
 
-    // Initialize contract with royalties
-    client.init(
-        &owner,
-        &String::from_str(&env, "Test NFTs"),
-        &String::from_str(&env, "TEST"),
-        &String::from_str(&env, "https://example.com"),
-        &royalties_bps,
+    let owner = Address::generate(&env);
+    let royalties_bps = 750u32; // 7.5%
+
+    // Construct with royalties
+    let contract_address = env.register(
+        NFTContract,
+        (
+            owner.clone(),
+            String::from_str(&env, "Test NFTs"),
+            String::from_str(&env, "TEST"),
+            String::from_str(&env, "https://example.com"),
+            royalties_bps,
+            false,
+            1u32,
+            None::<Symbol>,
+        ),
     );
+    let client = NFTContractClient::new(&env, &contract_address);
 
     // Verify royalties are stored correctly
     assert_eq!(client.get_royalties(), royalties_bps);
@@ -287,37 +255,27 @@ fn test_royalties() {
 #[test]
 fn test_multiple_mints() {
     let env = Env::default();
-    let (client, _) = create_nft_contract(&env);
+    env.mock_all_auths();
 
     let owner = Address::generate(&env);
     let minter = Address::generate(&env);
     let user1 = Address::generate(&env);
     let user2 = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
 
-    env.mock_all_auths();
-
-    // Initialize and setup
-    client.init(
-        &owner,
-        &String::from_str(&env, "Test NFTs"),
-        &String::from_str(&env, "TEST"),
-        &String::from_str(&env, "https://example.com"),
-        &100,
-    );
-
-    client.set_minter(&minter);
+    client.set_minter(&owner, &minter);
 
     // First mint
-    let first_token_id = client.mint(&user1, &3);
+    let first_token_id = client.mint(&minter, &user1, &3);
     assert_eq!(first_token_id, 1);
     assert_eq!(client.total_supply(), 3);
-    assert_eq!(client.balance_of(&user1), 3);
+    assert_eq!(client.balance(&user1), 3);
 
     // Second mint
-    let second_token_id = client.mint(&user2, &2);
+    let second_token_id = client.mint(&minter, &user2, &2);
     assert_eq!(second_token_id, 4);
     assert_eq!(client.total_supply(), 5);
-    assert_eq!(client.balance_of(&user2), 2);
+    assert_eq!(client.balance(&user2), 2);
 
     // Verify ownership
     assert_eq!(client.owner_of(&1), user1);
@@ -325,4 +283,862 @@ fn test_multiple_mints() {
     assert_eq!(client.owner_of(&3), user1);
     assert_eq!(client.owner_of(&4), user2);
     assert_eq!(client.owner_of(&5), user2);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_last_admin_cannot_be_removed() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let second_admin = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    // Owner is the only admin and cannot renounce
+    assert_eq!(client.admin_count(), 1);
+    assert!(client.is_last_admin(&owner));
+    assert!(client.try_renounce_admin().is_err());
+
+    // Holders of the plain admin role are not top-level admins
+    client.assign_role(&owner, &second_admin, &ADMIN_ROLE);
+    assert_eq!(client.admin_count(), 1);
+    assert!(!client.is_last_admin(&second_admin));
+    client.renounce_role(&second_admin, &ADMIN_ROLE);
+    assert!(!client.check_role(&second_admin, &ADMIN_ROLE));
+
+    // Handing over goes through a two-step transfer, never a renounce
+    client.transfer_admin_role(&second_admin, &(env.ledger().sequence() + 1000));
+    assert!(client.try_renounce_admin().is_err());
+    client.accept_admin_transfer();
+    assert_eq!(client.contract_admin(), second_admin);
+    assert_eq!(client.admin_count(), 1);
+    assert!(client.is_last_admin(&second_admin));
+    assert!(!client.is_last_admin(&owner));
+    assert!(client.try_renounce_admin().is_err());
+}
+
+#[test]
+fn test_transfer_history() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    let user3 = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    client.set_minter(&owner, &minter);
+    client.set_track_transfers(&true);
+    client.mint(&minter, &user1, &1);
+
+    // Transfer the token twice
+    client.transfer(&user1, &user2, &1);
+    client.transfer(&user2, &user3, &1);
+
+    let history = client.get_transfer_history(&1);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.get(0).unwrap().from, user1);
+    assert_eq!(history.get(0).unwrap().to, user2);
+    assert_eq!(history.get(1).unwrap().from, user2);
+    assert_eq!(history.get(1).unwrap().to, user3);
+}
+
+#[test]
+fn test_payout_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    // Defaults to the owner
+    assert_eq!(client.payout_address(), owner);
+
+    // Admin can route proceeds to a treasury instead
+    client.set_payout_address(&treasury);
+    assert_eq!(client.payout_address(), treasury);
+    assert_eq!(client.contract_admin(), owner);
+
+    // Royalties follow the payout address unless a royalty recipient is set
+    assert_eq!(client.royalty_recipient(), treasury);
+}
+
+#[test]
+fn test_sale_royalty_paid_to_payout_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = create_nft_contract_with_enforcement(&env, &owner, true);
+
+    let payment_token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    token::StellarAssetClient::new(&env, &payment_token).mint(&buyer, &10_000);
+
+    client.set_minter(&owner, &minter);
+    client.mint(&minter, &seller, &1);
+    client.set_payout_address(&treasury);
+
+    // The 1% royalty lands at the treasury, not the admin
+    client.sell(&seller, &buyer, &1, &10_000, &payment_token);
+
+    let payment = token::Client::new(&env, &payment_token);
+    assert_eq!(payment.balance(&treasury), 100);
+    assert_eq!(payment.balance(&owner), 0);
+    assert_eq!(payment.balance(&seller), 9_900);
+}
+
+#[test]
+fn test_royalty_recipient() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    // Defaults to the owner
+    assert_eq!(client.royalty_recipient(), owner);
+
+    client.set_royalty_recipient(&recipient);
+    assert_eq!(client.royalty_recipient(), recipient);
+
+    // royalty_info agrees with the recipient (100 bps of 10,000)
+    let (info_recipient, amount) = client.royalty_info(&1, &10_000i128);
+    assert_eq!(info_recipient, recipient);
+    assert_eq!(amount, 100i128);
+}
+
+#[test]
+fn test_minted_at() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let user = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    client.set_minter(&owner, &minter);
+
+    env.ledger().set_timestamp(1_700_000_000);
+    client.mint(&minter, &user, &2);
+    env.ledger().set_timestamp(1_700_000_500);
+    client.mint(&minter, &user, &1);
+
+    assert_eq!(client.minted_at(&1), 1_700_000_000);
+    assert_eq!(client.minted_at(&2), 1_700_000_000);
+    assert_eq!(client.minted_at(&3), 1_700_000_500);
+
+    // Unknown tokens are rejected
+    assert!(client.try_minted_at(&4).is_err());
+}
+
+#[test]
+fn test_next_token_id() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let user = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    assert_eq!(client.next_token_id(), 1);
+
+    client.set_minter(&owner, &minter);
+    let first = client.mint(&minter, &user, &3);
+    assert_eq!(first, 1);
+    assert_eq!(client.next_token_id(), 4);
+
+    client.mint(&minter, &user, &2);
+    assert_eq!(client.next_token_id(), 6);
+}
+
+#[test]
+fn test_grant_roles_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    let minter_role = symbol_short!("minter");
+    let minters = vec![
+        &env,
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+
+    client.grant_roles(&owner, &minters, &minter_role);
+
+    for minter in minters.iter() {
+        assert!(client.check_role(&minter, &minter_role));
+    }
+    assert_eq!(client.get_role_member_count(&minter_role), 3);
+}
+
+#[test]
+fn test_max_supply() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let user = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    client.set_minter(&owner, &minter);
+    assert_eq!(client.remaining_supply(), None);
+
+    client.set_max_supply(&Some(5));
+    assert_eq!(client.remaining_supply(), Some(5));
+
+    client.mint(&minter, &user, &3);
+    assert_eq!(client.remaining_supply(), Some(2));
+
+    // Minting past the cap is rejected
+    assert!(client.try_mint(&minter, &user, &3).is_err());
+    assert_eq!(client.total_supply(), 3);
+
+    client.mint(&minter, &user, &2);
+    assert_eq!(client.remaining_supply(), Some(0));
+}
+
+#[test]
+fn test_max_mint_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let user = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    client.set_minter(&owner, &minter);
+    assert_eq!(client.max_mint_batch(), 50);
+
+    // Exactly at the cap succeeds, one over fails without minting
+    client.mint(&minter, &user, &50);
+    assert!(client.try_mint(&minter, &user, &51).is_err());
+    assert_eq!(client.total_supply(), 50);
+
+    client.set_max_mint_batch(&5);
+    assert!(client.try_mint(&minter, &user, &6).is_err());
+    assert!(client.try_reserve(&minter, &user, &6).is_err());
+    client.mint(&minter, &user, &5);
+    assert_eq!(client.total_supply(), 55);
+}
+
+#[test]
+fn test_reserve_and_finalize() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let user = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    client.set_minter(&owner, &minter);
+    client.set_max_supply(&Some(5));
+
+    let first_id = client.reserve(&minter, &user, &3);
+    assert_eq!(first_id, 1);
+    assert_eq!(client.next_token_id(), 4);
+    assert_eq!(client.total_supply(), 0);
+    // Reserved ids count against the cap
+    assert_eq!(client.remaining_supply(), Some(2));
+    assert!(client.try_mint(&minter, &user, &3).is_err());
+
+    client.finalize_reservation(&minter, &first_id);
+    assert_eq!(client.total_supply(), 3);
+    assert_eq!(client.owner_of(&3), user);
+    assert!(client.get_reservation(&first_id).is_none());
+    assert_eq!(client.remaining_supply(), Some(2));
+}
+
+#[test]
+fn test_reserve_and_cancel() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let user = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    client.set_minter(&owner, &minter);
+    client.set_max_supply(&Some(5));
+
+    let first_id = client.reserve(&minter, &user, &4);
+    assert_eq!(client.remaining_supply(), Some(1));
+
+    client.cancel_reservation(&minter, &first_id);
+    assert!(client.get_reservation(&first_id).is_none());
+    assert_eq!(client.total_supply(), 0);
+    // Cancelled ids free up the cap but are not reused
+    assert_eq!(client.remaining_supply(), Some(5));
+    assert_eq!(client.mint(&minter, &user, &1), 5);
+
+    // A cancelled reservation cannot be finalized
+    assert!(client.try_finalize_reservation(&minter, &first_id).is_err());
+}
+
+#[test]
+fn test_double_finalize_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let user = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    client.set_minter(&owner, &minter);
+
+    let first_id = client.reserve(&minter, &user, &2);
+    client.finalize_reservation(&minter, &first_id);
+    assert!(client.try_finalize_reservation(&minter, &first_id).is_err());
+    assert_eq!(client.total_supply(), 2);
+}
+
+#[test]
+fn test_mint_delegate() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let delegate = Address::generate(&env);
+    let random = Address::generate(&env);
+    let user = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    assert_eq!(client.mint_delegate(), None);
+    assert!(client.try_mint(&delegate, &user, &1).is_err());
+
+    client.set_mint_delegate(&Some(delegate.clone()));
+    assert_eq!(client.mint_delegate(), Some(delegate.clone()));
+
+    // The delegate mints without holding the minter role
+    assert_eq!(client.mint(&delegate, &user, &2), 1);
+    assert_eq!(client.balance(&user), 2);
+
+    // Anyone else is still rejected
+    assert!(client.try_mint(&random, &user, &1).is_err());
+    assert_eq!(client.total_supply(), 2);
+}
+
+#[test]
+fn test_sell_with_enforced_royalties() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = create_nft_contract_with_enforcement(&env, &owner, true);
+    assert!(client.royalties_enforced());
+
+    let payment_token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    token::StellarAssetClient::new(&env, &payment_token).mint(&buyer, &10_000);
+
+    client.set_minter(&owner, &minter);
+    client.mint(&minter, &seller, &1);
+
+    // 1% royalty goes to the royalty recipient (the admin by default)
+    client.sell(&seller, &buyer, &1, &10_000, &payment_token);
+
+    let payment = token::Client::new(&env, &payment_token);
+    assert_eq!(client.owner_of(&1), buyer);
+    assert_eq!(payment.balance(&owner), 100);
+    assert_eq!(payment.balance(&seller), 9_900);
+    assert_eq!(payment.balance(&buyer), 0);
+}
+
+#[test]
+fn test_sell_with_secondary_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let platform = Address::generate(&env);
+    let client = create_nft_contract_with_enforcement(&env, &owner, true);
+
+    let payment_token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    token::StellarAssetClient::new(&env, &payment_token).mint(&buyer, &10_000);
+
+    client.set_minter(&owner, &minter);
+    client.mint(&minter, &seller, &1);
+    client.set_secondary_fee(&250, &Some(platform.clone()));
+    assert_eq!(client.secondary_fee(), (250, Some(platform.clone())));
+    assert!(client.try_set_secondary_fee(&100, &None).is_err());
+
+    // 1% royalty to the admin, 2.5% to the platform, the rest to the seller
+    client.sell(&seller, &buyer, &1, &10_000, &payment_token);
+
+    let payment = token::Client::new(&env, &payment_token);
+    assert_eq!(client.owner_of(&1), buyer);
+    assert_eq!(payment.balance(&owner), 100);
+    assert_eq!(payment.balance(&platform), 250);
+    assert_eq!(payment.balance(&seller), 9_650);
+    assert_eq!(payment.balance(&buyer), 0);
+}
+
+#[test]
+fn test_secondary_fee_capped_at_seller_proceeds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let platform = Address::generate(&env);
+    let client = create_nft_contract_with_enforcement(&env, &owner, true);
+
+    let payment_token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    token::StellarAssetClient::new(&env, &payment_token).mint(&buyer, &10_000);
+
+    client.set_minter(&owner, &minter);
+    client.mint(&minter, &seller, &1);
+    client.set_secondary_fee(&10_000, &Some(platform.clone()));
+
+    // Royalty plus fee would exceed the price; the fee takes only what is left
+    client.sell(&seller, &buyer, &1, &10_000, &payment_token);
+
+    let payment = token::Client::new(&env, &payment_token);
+    assert_eq!(payment.balance(&owner), 100);
+    assert_eq!(payment.balance(&platform), 9_900);
+    assert_eq!(payment.balance(&seller), 0);
+}
+
+#[test]
+fn test_sell_requires_enforced_royalties() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+    assert!(!client.royalties_enforced());
+
+    let payment_token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    token::StellarAssetClient::new(&env, &payment_token).mint(&buyer, &10_000);
+
+    client.set_minter(&owner, &minter);
+    client.mint(&minter, &seller, &1);
+
+    // Enforced sales are unavailable; the token moves through a plain transfer instead
+    assert!(client.try_sell(&seller, &buyer, &1, &10_000, &payment_token).is_err());
+    client.transfer(&seller, &buyer, &1);
+    assert_eq!(client.owner_of(&1), buyer);
+    assert_eq!(token::Client::new(&env, &payment_token).balance(&buyer), 10_000);
+}
+
+#[test]
+fn test_roles_of() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let account = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    let minter_role = symbol_short!("minter");
+    let curator_role = symbol_short!("curator");
+
+    assert_eq!(client.roles_of(&account).len(), 0);
+
+    client.set_minter(&owner, &account);
+    client.assign_role(&owner, &account, &curator_role);
+    assert_eq!(client.roles_of(&account), vec![&env, minter_role.clone(), curator_role.clone()]);
+
+    client.remove_role(&owner, &account, &minter_role);
+    assert_eq!(client.roles_of(&account), vec![&env, curator_role]);
+}
+
+// Role changes are reported by the OpenZeppelin access control events, topics (name, role, account)
+fn has_role_event(env: &Env, contract: &Address, name: &str, account: &Address, role: &Symbol) -> bool {
+    let expected = (Symbol::new(env, name), role.clone(), account.clone()).into_val(env);
+    env.events().all().iter().any(|(emitter, topics, _)| emitter == *contract && topics == expected)
+}
+
+#[test]
+fn test_role_events() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let account = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    let minter_role = symbol_short!("minter");
+    let curator_role = symbol_short!("curator");
+
+    client.set_minter(&owner, &account);
+    assert!(has_role_event(&env, &client.address, "role_granted", &account, &minter_role));
+
+    client.assign_role(&owner, &account, &curator_role);
+    assert!(has_role_event(&env, &client.address, "role_granted", &account, &curator_role));
+    assert!(!has_role_event(&env, &client.address, "role_granted", &account, &minter_role));
+
+    client.remove_role(&owner, &account, &curator_role);
+    assert!(has_role_event(&env, &client.address, "role_revoked", &account, &curator_role));
+    assert!(!has_role_event(&env, &client.address, "role_granted", &account, &curator_role));
+
+    client.renounce_role(&account, &minter_role);
+    assert!(has_role_event(&env, &client.address, "role_revoked", &account, &minter_role));
+}
+
+#[test]
+fn test_token_name() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    // Defaults to the collection name
+    assert_eq!(client.token_name(&42), String::from_str(&env, "Test NFTs #42"));
+    assert_eq!(client.token_name(&0), String::from_str(&env, "Test NFTs #0"));
+    assert_eq!(client.token_name(&u32::MAX), String::from_str(&env, "Test NFTs #4294967295"));
+
+    client.set_name_template(&Some(String::from_str(&env, "Stellar Wizards")));
+    assert_eq!(client.token_name(&42), String::from_str(&env, "Stellar Wizards #42"));
+
+    client.set_name_template(&None);
+    assert_eq!(client.name_template(), None);
+    assert_eq!(client.token_name(&7), String::from_str(&env, "Test NFTs #7"));
+}
+
+fn register_with_name<'a>(env: &Env, owner: &Address, name: &str) -> NFTContractClient<'a> {
+    let contract_address = env.register(
+        NFTContract,
+        (
+            owner.clone(),
+            String::from_str(env, name),
+            String::from_str(env, "TEST"),
+            String::from_str(env, "https://example.com"),
+            100u32,
+            false,
+            1u32,
+            None::<Symbol>,
+        ),
+    );
+    NFTContractClient::new(env, &contract_address)
+}
+
+#[test]
+fn test_collection_name_at_limit() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let name = "A".repeat(MAX_TOKEN_NAME_BASE as usize);
+    let client = register_with_name(&env, &owner, &name);
+
+    // The longest allowed name still composes the longest token name
+    assert_eq!(client.token_name(&u32::MAX).len(), MAX_TOKEN_NAME_BASE + 12);
+}
+
+#[test]
+#[should_panic(expected = "Collection name too long")]
+fn test_collection_name_too_long() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let name = "A".repeat(MAX_TOKEN_NAME_BASE as usize + 1);
+    register_with_name(&env, &owner, &name);
+}
+
+#[test]
+fn test_mint_start_id_and_reserved_mints() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let team = Address::generate(&env);
+    let user = Address::generate(&env);
+    let client = create_nft_contract_with_options(&env, &owner, false, 101);
+
+    client.set_minter(&owner, &minter);
+    assert_eq!(client.next_token_id(), 101);
+    assert_eq!(client.total_supply(), 0);
+
+    // Public mints start above the reserved range
+    assert_eq!(client.mint(&minter, &user, &2), 101);
+    assert_eq!(client.owner_of(&101), user);
+
+    // The admin fills reserved ids individually
+    client.mint_reserved(&owner, &team, &1);
+    client.mint_reserved(&owner, &team, &100);
+    assert_eq!(client.owner_of(&1), team);
+    assert_eq!(client.owner_of(&100), team);
+    assert_eq!(client.total_supply(), 4);
+    assert_eq!(client.next_token_id(), 103);
+
+    // Outside the reserved range or already minted
+    assert!(client.try_mint_reserved(&owner, &team, &101).is_err());
+    assert!(client.try_mint_reserved(&owner, &team, &0).is_err());
+    assert!(client.try_mint_reserved(&owner, &team, &1).is_err());
+}
+
+#[test]
+fn test_allowlist_expiry() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    client.set_allowlisted(&vec![&env, buyer.clone()], &5);
+    client.set_allowlist_expiry(&Some(1_000));
+    assert_eq!(client.allowlist_expiry(), Some(1_000));
+
+    // Before (and at) the expiry allowlisted accounts can mint
+    env.ledger().set_timestamp(1_000);
+    assert_eq!(client.mint_allowlisted(&buyer, &2), 1);
+    assert_eq!(client.owner_of(&1), buyer);
+    assert!(client.try_mint_allowlisted(&outsider, &1).is_err());
+
+    // After it they are rejected
+    env.ledger().set_timestamp(1_001);
+    assert!(client.try_mint_allowlisted(&buyer, &1).is_err());
+    assert_eq!(client.total_supply(), 2);
+
+    // The admin can extend the window
+    client.set_allowlist_expiry(&Some(2_000));
+    client.mint_allowlisted(&buyer, &1);
+    assert_eq!(client.total_supply(), 3);
+}
+
+#[test]
+fn test_allowlist_quota() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+
+    client.set_allowlisted(&vec![&env, buyer.clone()], &3);
+    assert_eq!(client.allowlist_remaining(&buyer), 3);
+
+    // Each presale mint spends from the quota and cannot overdraw it
+    client.mint_allowlisted(&buyer, &2);
+    assert_eq!(client.allowlist_remaining(&buyer), 1);
+    assert!(client.try_mint_allowlisted(&buyer, &2).is_err());
+
+    // Spending the last of it takes the account off the allowlist
+    client.mint_allowlisted(&buyer, &1);
+    assert!(!client.is_allowlisted(&buyer));
+    assert!(client.try_mint_allowlisted(&buyer, &1).is_err());
+    assert_eq!(client.total_supply(), 3);
+}
+
+#[test]
+fn test_transfer_lock_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let holder = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+    client.set_minter(&owner, &minter);
+
+    client.set_transfer_lock(&owner, &3_600);
+    assert_eq!(client.transfer_lock_secs(), 3_600);
+
+    env.ledger().set_timestamp(1_000);
+    client.mint(&minter, &holder, &1);
+
+    // Still inside the lock window
+    env.ledger().set_timestamp(4_599);
+    assert!(client.try_transfer(&holder, &buyer, &1).is_err());
+    assert_eq!(client.owner_of(&1), holder);
+
+    // Transferable once minted_at + transfer_lock_secs is reached
+    env.ledger().set_timestamp(4_600);
+    client.transfer(&holder, &buyer, &1);
+    assert_eq!(client.owner_of(&1), buyer);
+}
+
+#[test]
+fn test_sell_respects_transfer_lock() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = create_nft_contract_with_enforcement(&env, &owner, true);
+    client.set_minter(&owner, &minter);
+
+    let payment_token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    token::StellarAssetClient::new(&env, &payment_token).mint(&buyer, &10_000);
+
+    client.set_transfer_lock(&owner, &3_600);
+    env.ledger().set_timestamp(1_000);
+    client.mint(&minter, &seller, &1);
+
+    // A sale is a transfer too, so it waits out the lock like one
+    env.ledger().set_timestamp(4_599);
+    assert!(client.try_sell(&seller, &buyer, &1, &10_000, &payment_token).is_err());
+    assert_eq!(client.owner_of(&1), seller);
+    assert_eq!(token::Client::new(&env, &payment_token).balance(&buyer), 10_000);
+
+    env.ledger().set_timestamp(4_600);
+    client.sell(&seller, &buyer, &1, &10_000, &payment_token);
+    assert_eq!(client.owner_of(&1), buyer);
+}
+
+#[test]
+fn test_denied_recipients() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let holder = Address::generate(&env);
+    let denied = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+    client.set_minter(&owner, &minter);
+
+    client.set_denied(&owner, &denied, &true);
+    assert!(client.is_denied(&denied));
+
+    // Denied addresses cannot be minted to
+    assert!(client.try_mint(&minter, &denied, &1).is_err());
+    assert_eq!(client.total_supply(), 0);
+
+    // Nor receive transfers, direct or approved
+    client.mint(&minter, &holder, &2);
+    assert!(client.try_transfer(&holder, &denied, &1).is_err());
+    client.approve(&holder, &minter, &2, &1000);
+    assert!(client.try_transfer_from(&minter, &holder, &denied, &2).is_err());
+    assert_eq!(client.owner_of(&1), holder);
+    assert_eq!(client.owner_of(&2), holder);
+
+    // Lifting the denial restores both
+    client.set_denied(&owner, &denied, &false);
+    client.transfer(&holder, &denied, &1);
+    assert_eq!(client.owner_of(&1), denied);
+}
+
+#[test]
+fn test_sell_to_denied_buyer_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let denied = Address::generate(&env);
+    let client = create_nft_contract_with_enforcement(&env, &owner, true);
+    client.set_minter(&owner, &minter);
+    client.mint(&minter, &seller, &1);
+
+    let payment_token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    token::StellarAssetClient::new(&env, &payment_token).mint(&denied, &10_000);
+
+    // A denied address cannot buy its way around the transfer check
+    client.set_denied(&owner, &denied, &true);
+    assert!(client.try_sell(&seller, &denied, &1, &10_000, &payment_token).is_err());
+    assert_eq!(client.owner_of(&1), seller);
+    assert_eq!(token::Client::new(&env, &payment_token).balance(&denied), 10_000);
+}
+
+#[test]
+fn test_batch_burn() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let holder = Address::generate(&env);
+    let other = Address::generate(&env);
+    let client = create_initialized_nft_contract(&env, &owner);
+    client.set_minter(&owner, &minter);
+
+    client.mint(&minter, &holder, &4);
+    client.mint(&minter, &other, &1);
+    assert_eq!(client.total_supply(), 5);
+
+    client.batch_burn(&holder, &vec![&env, 1u32, 3u32]);
+    assert_eq!(client.total_supply(), 3);
+    assert_eq!(client.burned_count(), 2);
+    assert_eq!(client.balance(&holder), 2);
+    assert!(client.try_owner_of(&1).is_err());
+    assert!(client.try_owner_of(&3).is_err());
+    assert_eq!(client.owner_of(&2), holder);
+    assert_eq!(client.owner_of(&4), holder);
+
+    // A token the sender doesn't own reverts the whole batch
+    assert!(client.try_batch_burn(&holder, &vec![&env, 2u32, 5u32]).is_err());
+    assert_eq!(client.owner_of(&2), holder);
+    assert_eq!(client.total_supply(), 3);
+}
+
+#[test]
+fn test_custom_admin_role() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let minter = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let manager_role = symbol_short!("manager");
+
+    let contract_address = env.register(
+        NFTContract,
+        (
+            owner.clone(),
+            String::from_str(&env, "Test NFTs"),
+            String::from_str(&env, "TEST"),
+            String::from_str(&env, "https://example.com"),
+            100u32,
+            false,
+            1u32,
+            Some(manager_role.clone()),
+        ),
+    );
+    let client = NFTContractClient::new(&env, &contract_address);
+
+    assert_eq!(client.admin_role(), manager_role);
+    assert_eq!(client.get_role_admin(&MINTER_ROLE), Some(manager_role.clone()));
+
+    // Holders of the custom role administer minters
+    client.assign_role(&owner, &manager, &manager_role);
+    client.assign_role(&manager, &minter, &MINTER_ROLE);
+    assert!(client.has_role(&minter, &MINTER_ROLE).is_some());
+    assert!(client.try_assign_role(&outsider, &outsider, &MINTER_ROLE).is_err());
+
+    client.remove_role(&manager, &minter, &MINTER_ROLE);
+    assert!(client.has_role(&minter, &MINTER_ROLE).is_none());
+
+    // The custom role administers minters without making its holders top-level admins
+    assert_eq!(client.admin_count(), 1);
+    assert!(!client.is_last_admin(&manager));
+}
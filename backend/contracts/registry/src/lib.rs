@@ -1,7 +1,10 @@
 #![no_std]
+// Entry points like log_and_route take their inputs flat so callers can pass plain ScVals;
+// crate-wide because the client and args code #[contractimpl] generates sits outside the impl
+#![allow(clippy::too_many_arguments)]
 use soroban_sdk::{
     contract, contractimpl, contracttype, contracterror, contractmeta,
-    Address, Env, String, Vec, log, symbol_short,
+    Address, Env, String, Symbol, Vec, log, symbol_short,
     token,
 };
 
@@ -23,6 +26,14 @@ pub enum RegistryError {
     RecordNotFound = 4,
     ContractPaused = 5,
     InvalidFeeRate = 6,
+    RefundExceedsFee = 7,
+    RecordAlreadyExists = 8,
+    InvalidTags = 9,
+    ConfigChangeCooldown = 10,
+    BatchTooLarge = 11,
+    UserSuspended = 12,
+    TxRefTooLong = 13,
+    InvalidLockTime = 14,
 }
 
 #[contracttype]
@@ -30,6 +41,7 @@ pub enum RegistryError {
 pub enum ActionType {
     NFT,
     DEFI,
+    STAKE,
 }
 
 #[contracttype]
@@ -45,6 +57,10 @@ pub struct ActionRecord {
     pub tx_refs: Vec<String>,
     pub fee_amount: i128,
     pub total_amount: i128,
+    pub fee_recipient_override: Option<Address>,
+    pub tags: Vec<String>,
+    pub counterparty: Option<Address>,
+    pub lock_until: Option<u64>, // STAKE records: minimum hold, as a ledger timestamp
 }
 
 #[contracttype]
@@ -54,6 +70,15 @@ pub struct Config {
     pub fee_bps: u32,        // basis points (200 = 2%)
     pub fee_wallet: Address,
     pub paused: bool,
+    pub emit_events: bool,
+    pub grace_until: u64,    // no fees charged before this ledger timestamp
+    pub max_amount: Option<i128>,
+    pub event_prefix: Symbol, // leading event topic, distinguishes deployments
+    pub config_change_cooldown_secs: u64, // minimum gap between fee changes
+    pub min_fee: i128,       // floor for fee-bearing actions (0 = none)
+    pub treasury_split_bps: u32, // share of each fee routed to the treasury
+    pub treasury_address: Option<Address>,
+    pub emit_full_event: bool, // publish the whole ActionRecord as action_full instead of action
 }
 
 #[contracttype]
@@ -62,9 +87,73 @@ pub enum DataKey {
     NextId,
     Record(u64),
     UserRecords(Address),
+    TotalFeesCollected,
+    UserLastActivity(Address),
+    TotalVolume,
+    RefundedAmount(u64),
+    LastConfigChange,
+    ConfigHistory,
+    SuspendedUser(Address),
+    CounterpartyRecords(Address),
+    UserCount,
+    UserVolume(Address),
+    UserFeesPaid(Address),
+    VolumeTiers,
+    RecipientFee(u64), // fee share kept by the recipient, when the treasury took a cut
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VolumeTier {
+    pub min_volume: i128, // cumulative routed volume at which the tier starts
+    pub fee_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UserDashboard {
+    pub record_count: u32,
+    pub total_volume: i128,
+    pub total_fees_paid: i128,
+    pub last_activity: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StorageStats {
+    pub total_records: u64,     // persistent, one entry per record
+    pub user_count: u64,        // persistent, one record index per user
+    pub config_history_len: u32, // instance, bounded by MAX_CONFIG_HISTORY
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordPage {
+    pub records: Vec<ActionRecord>,
+    pub has_more: bool,
+    pub next_start: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigSnapshot {
+    pub config: Config,
+    pub timestamp: u64,
 }
 
 const MAX_FEE_BPS: u32 = 1000; // 10% maximum fee
+const MAX_TREASURY_SPLIT_BPS: u32 = 5000; // at most half of each fee to the treasury
+const MAX_TAGS: u32 = 5;
+const MAX_TAG_LEN: u32 = 32;
+const MAX_CONFIG_HISTORY: u32 = 10;
+const MAX_BATCH_IDS: u32 = 50;
+const MAX_VOLUME_TIERS: u32 = 10;
+pub const MAX_TX_REF_LEN: u32 = 128; // room for a 64-char tx hash plus a chain/explorer prefix
+// Instance storage only holds bounded entries (config, counters, capped history), so it is
+// bumped as a whole; records and per-user indexes live in persistent storage and are bumped
+// individually when written
+pub const INSTANCE_TTL_EXTENSION: u32 = 518_400; // ~30 days of ledgers
+pub const RECORD_TTL_EXTENSION: u32 = 1_555_200; // ~90 days of ledgers
 
 #[contract]
 pub struct StellarWizardRegistry;
@@ -77,6 +166,7 @@ impl StellarWizardRegistry {
         owner: Address,
         fee_bps: u32,
         fee_wallet: Address,
+        event_prefix: Symbol,
     ) -> Result<(), RegistryError> {
         if env.storage().instance().has(&DataKey::Config) {
             panic!("Contract already initialized");
@@ -91,6 +181,15 @@ impl StellarWizardRegistry {
             fee_bps,
             fee_wallet,
             paused: false,
+            emit_events: true,
+            grace_until: 0,
+            max_amount: None,
+            event_prefix,
+            config_change_cooldown_secs: 0,
+            min_fee: 0,
+            treasury_split_bps: 0,
+            treasury_address: None,
+            emit_full_event: false,
         };
 
         env.storage().instance().set(&DataKey::Config, &config);
@@ -117,6 +216,40 @@ impl StellarWizardRegistry {
         network: String,
         total_amount: i128,
         token_address: Address,
+        fee_recipient_override: Option<Address>,
+        tags: Vec<String>,
+        counterparty: Option<Address>,
+    ) -> Result<u64, RegistryError> {
+        Self::route_action(
+            env,
+            user,
+            action_type,
+            plan_hash,
+            payload_ref,
+            network,
+            total_amount,
+            token_address,
+            fee_recipient_override,
+            tags,
+            counterparty,
+            None,
+        )
+    }
+
+    // Shared by log_and_route and log_stake so the record is complete before any event fires
+    fn route_action(
+        env: Env,
+        user: Address,
+        action_type: ActionType,
+        plan_hash: String,
+        payload_ref: String,
+        network: String,
+        total_amount: i128,
+        token_address: Address,
+        fee_recipient_override: Option<Address>,
+        tags: Vec<String>,
+        counterparty: Option<Address>,
+        lock_until: Option<u64>,
     ) -> Result<u64, RegistryError> {
         let config = Self::get_config(&env)?;
         
@@ -127,12 +260,39 @@ impl StellarWizardRegistry {
         // Require user authorization
         user.require_auth();
 
+        if Self::is_user_suspended(env.clone(), user.clone()) {
+            return Err(RegistryError::UserSuspended);
+        }
+
+        // Redirecting the fee requires the owner's sign-off
+        if fee_recipient_override.is_some() {
+            config.owner.require_auth();
+        }
+
         if total_amount <= 0 {
             return Err(RegistryError::InvalidAmount);
         }
 
-        // Calculate fee
-        let fee_amount = (total_amount * config.fee_bps as i128) / 10000i128;
+        if let Some(max_amount) = config.max_amount {
+            if total_amount > max_amount {
+                return Err(RegistryError::InvalidAmount);
+            }
+        }
+
+        if tags.len() > MAX_TAGS || tags.iter().any(|tag| tag.is_empty() || tag.len() > MAX_TAG_LEN) {
+            return Err(RegistryError::InvalidTags);
+        }
+
+        // Calculate fee (waived during the grace period, tiered by the user's prior volume,
+        // floored at min_fee but never above the MAX_FEE_BPS share of the amount)
+        let fee_amount = if config.fee_bps == 0 || env.ledger().timestamp() < config.grace_until {
+            0i128
+        } else {
+            let fee_bps = Self::get_user_fee_bps(env.clone(), user.clone())?;
+            let fee = (total_amount * fee_bps as i128) / 10000i128;
+            let max_fee = (total_amount * MAX_FEE_BPS as i128) / 10000i128;
+            fee.max(config.min_fee.min(max_fee))
+        };
         
         // Get next ID
         let id = env.storage().instance().get(&DataKey::NextId).unwrap_or(1u64);
@@ -150,6 +310,10 @@ impl StellarWizardRegistry {
             tx_refs: Vec::new(&env),
             fee_amount,
             total_amount,
+            fee_recipient_override: fee_recipient_override.clone(),
+            tags,
+            counterparty: counterparty.clone(),
+            lock_until,
         };
 
         // Store record
@@ -160,26 +324,96 @@ impl StellarWizardRegistry {
             .persistent()
             .get(&DataKey::UserRecords(user.clone()))
             .unwrap_or(Vec::new(&env));
+        if user_records.is_empty() {
+            count_new_user(&env);
+        }
         user_records.push_back(id);
         env.storage().persistent().set(&DataKey::UserRecords(user.clone()), &user_records);
+        env.storage().persistent().set(&DataKey::UserLastActivity(user.clone()), &env.ledger().timestamp());
+        add_user_totals(&env, &user, total_amount, fee_amount)?;
+
+        // Update counterparty index
+        if let Some(counterparty) = counterparty {
+            let mut counterparty_records: Vec<u64> = env.storage()
+                .persistent()
+                .get(&DataKey::CounterpartyRecords(counterparty.clone()))
+                .unwrap_or(Vec::new(&env));
+            counterparty_records.push_back(id);
+            env.storage().persistent().set(&DataKey::CounterpartyRecords(counterparty), &counterparty_records);
+        }
+
+        // Update global routed volume
+        let total_volume: i128 = env.storage().instance()
+            .get(&DataKey::TotalVolume)
+            .unwrap_or(0i128);
+        let total_volume = total_volume
+            .checked_add(total_amount)
+            .ok_or(RegistryError::InvalidAmount)?;
+        env.storage().instance().set(&DataKey::TotalVolume, &total_volume);
 
-        // Transfer fee if amount > 0
+        // Transfer fee if amount > 0, routing the treasury's share separately
         if fee_amount > 0 {
+            let fee_recipient = fee_recipient_override.unwrap_or(config.fee_wallet.clone());
             let token_client = token::Client::new(&env, &token_address);
-            token_client.transfer(&user, &config.fee_wallet, &fee_amount);
 
-            // Emit fee paid event
-            env.events().publish(
-                (symbol_short!("fee_paid"),),
-                (id, config.fee_wallet.clone(), fee_amount)
-            );
+            let treasury_share = match &config.treasury_address {
+                Some(_) => (fee_amount * config.treasury_split_bps as i128) / 10000i128,
+                None => 0i128,
+            };
+            let wallet_share = fee_amount - treasury_share;
+            if treasury_share > 0 {
+                env.storage().persistent().set(&DataKey::RecipientFee(id), &wallet_share);
+            }
+
+            if wallet_share > 0 {
+                token_client.transfer(&user, &fee_recipient, &wallet_share);
+
+                // Only fees landing in the fee wallet count towards reconciliation
+                if fee_recipient == config.fee_wallet {
+                    let total_fees: i128 = env.storage().instance()
+                        .get(&DataKey::TotalFeesCollected)
+                        .unwrap_or(0i128);
+                    let total_fees = total_fees
+                        .checked_add(wallet_share)
+                        .ok_or(RegistryError::InvalidAmount)?;
+                    env.storage().instance().set(&DataKey::TotalFeesCollected, &total_fees);
+                }
+
+                // Emit fee paid event
+                if config.emit_events {
+                    env.events().publish(
+                        (config.event_prefix.clone(), symbol_short!("fee_paid")),
+                        (id, fee_recipient, wallet_share)
+                    );
+                }
+            }
+
+            if let (Some(treasury), true) = (&config.treasury_address, treasury_share > 0) {
+                token_client.transfer(&user, treasury, &treasury_share);
+
+                if config.emit_events {
+                    env.events().publish(
+                        (config.event_prefix.clone(), symbol_short!("treasury")),
+                        (id, treasury.clone(), treasury_share)
+                    );
+                }
+            }
         }
 
+        bump_record_ttl(&env, &record);
+
         // Emit action logged event
-        env.events().publish(
-            (symbol_short!("action"),),
-            (id, user, action_type, plan_hash, fee_amount)
-        );
+        if config.emit_events && config.emit_full_event {
+            env.events().publish(
+                (config.event_prefix.clone(), Symbol::new(&env, "action_full")),
+                record
+            );
+        } else if config.emit_events {
+            env.events().publish(
+                (config.event_prefix.clone(), symbol_short!("action")),
+                (id, user, action_type, plan_hash, fee_amount)
+            );
+        }
 
         log!(&env, "Action logged with ID: {}, fee: {}", id, fee_amount);
 
@@ -195,6 +429,10 @@ impl StellarWizardRegistry {
     ) -> Result<(), RegistryError> {
         user.require_auth();
 
+        if tx_ref.len() > MAX_TX_REF_LEN {
+            return Err(RegistryError::TxRefTooLong);
+        }
+
         let mut record: ActionRecord = env.storage()
             .persistent()
             .get(&DataKey::Record(id))
@@ -207,12 +445,134 @@ impl StellarWizardRegistry {
 
         record.tx_refs.push_back(tx_ref.clone());
         env.storage().persistent().set(&DataKey::Record(id), &record);
+        bump_record_ttl(&env, &record);
 
         log!(&env, "TX ref added to record {}: {}", id, tx_ref);
 
         Ok(())
     }
 
+    /// Write a historical record without routing fees or emitting an action event (owner only)
+    pub fn import_record(env: Env, record: ActionRecord) -> Result<(), RegistryError> {
+        let config = Self::get_config(&env)?;
+        config.owner.require_auth();
+
+        let id = record.id;
+        if id == 0 || env.storage().persistent().has(&DataKey::Record(id)) {
+            return Err(RegistryError::RecordAlreadyExists);
+        }
+
+        // Keep future ids clear of imported ones
+        let after_id = id.checked_add(1).ok_or(RegistryError::InvalidAmount)?;
+        let next_id: u64 = env.storage().instance().get(&DataKey::NextId).unwrap_or(1u64);
+        env.storage().instance().set(&DataKey::NextId, &next_id.max(after_id));
+
+        env.storage().persistent().set(&DataKey::Record(id), &record);
+
+        // Imported ids can predate existing ones, so keep the indexes sorted
+        if !env.storage().persistent().has(&DataKey::UserRecords(record.user.clone())) {
+            count_new_user(&env);
+        }
+        insert_record_id(&env, DataKey::UserRecords(record.user.clone()), id);
+        if let Some(counterparty) = record.counterparty.clone() {
+            insert_record_id(&env, DataKey::CounterpartyRecords(counterparty), id);
+        }
+
+        let last_activity = Self::get_user_last_activity(env.clone(), record.user.clone());
+        if record.timestamp > last_activity {
+            env.storage().persistent().set(&DataKey::UserLastActivity(record.user.clone()), &record.timestamp);
+        }
+        add_user_totals(&env, &record.user, record.total_amount, record.fee_amount)?;
+        bump_record_ttl(&env, &record);
+
+        // Update global routed volume
+        let total_volume = Self::get_total_volume(env.clone())
+            .checked_add(record.total_amount)
+            .ok_or(RegistryError::InvalidAmount)?;
+        env.storage().instance().set(&DataKey::TotalVolume, &total_volume);
+
+        if config.emit_events {
+            env.events().publish(
+                (config.event_prefix.clone(), symbol_short!("imported")),
+                (id, record.user)
+            );
+        }
+
+        log!(&env, "Record {} imported", id);
+
+        Ok(())
+    }
+
+    /// Refund part of a record's fee from whoever received it (owner only).
+    /// The treasury's cut is not refundable, so refunds are capped at the recipient's share
+    pub fn refund_partial(
+        env: Env,
+        id: u64,
+        amount: i128,
+        token_address: Address,
+    ) -> Result<(), RegistryError> {
+        let config = Self::get_config(&env)?;
+        config.owner.require_auth();
+
+        if amount <= 0 {
+            return Err(RegistryError::InvalidAmount);
+        }
+
+        let record: ActionRecord = env.storage()
+            .persistent()
+            .get(&DataKey::Record(id))
+            .ok_or(RegistryError::RecordNotFound)?;
+
+        let refunded = Self::get_refunded_amount(env.clone(), id);
+        let refunded = refunded
+            .checked_add(amount)
+            .ok_or(RegistryError::InvalidAmount)?;
+        let refundable: i128 = env.storage()
+            .persistent()
+            .get(&DataKey::RecipientFee(id))
+            .unwrap_or(record.fee_amount);
+        if refunded > refundable {
+            return Err(RegistryError::RefundExceedsFee);
+        }
+
+        let fee_recipient = record.fee_recipient_override.clone().unwrap_or(config.fee_wallet.clone());
+        let token_client = token::Client::new(&env, &token_address);
+        token_client.transfer(&fee_recipient, &record.user, &amount);
+
+        env.storage().persistent().set(&DataKey::RefundedAmount(id), &refunded);
+        bump_record_ttl(&env, &record);
+
+        // Refunds out of the fee wallet no longer count as collected
+        if fee_recipient == config.fee_wallet {
+            let total_fees: i128 = env.storage().instance()
+                .get(&DataKey::TotalFeesCollected)
+                .unwrap_or(0i128);
+            let total_fees = total_fees
+                .checked_sub(amount)
+                .ok_or(RegistryError::InvalidAmount)?;
+            env.storage().instance().set(&DataKey::TotalFeesCollected, &total_fees);
+        }
+
+        if config.emit_events {
+            env.events().publish(
+                (config.event_prefix.clone(), symbol_short!("refund")),
+                (id, record.user, amount)
+            );
+        }
+
+        log!(&env, "Refunded {} of record {} fee", amount, id);
+
+        Ok(())
+    }
+
+    /// Get the cumulative amount refunded for a record
+    pub fn get_refunded_amount(env: Env, id: u64) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RefundedAmount(id))
+            .unwrap_or(0i128)
+    }
+
     /// Get a specific record by ID
     pub fn get_record(env: Env, id: u64) -> Result<ActionRecord, RegistryError> {
         env.storage()
@@ -221,6 +581,76 @@ impl StellarWizardRegistry {
             .ok_or(RegistryError::RecordNotFound)
     }
 
+    /// Log a STAKE action held until the `lock_until` ledger timestamp, routed like log_and_route
+    pub fn log_stake(
+        env: Env,
+        user: Address,
+        plan_hash: String,
+        payload_ref: String,
+        network: String,
+        total_amount: i128,
+        token_address: Address,
+        lock_until: u64,
+    ) -> Result<u64, RegistryError> {
+        if lock_until <= env.ledger().timestamp() {
+            return Err(RegistryError::InvalidLockTime);
+        }
+
+        Self::route_action(
+            env.clone(),
+            user,
+            ActionType::STAKE,
+            plan_hash,
+            payload_ref,
+            network,
+            total_amount,
+            token_address,
+            None,
+            Vec::new(&env),
+            None,
+            Some(lock_until),
+        )
+    }
+
+    /// Whether a record's minimum hold is still running (false without a lock)
+    pub fn is_locked(env: Env, id: u64) -> Result<bool, RegistryError> {
+        let record = Self::get_record(env.clone(), id)?;
+        Ok(record.lock_until.is_some_and(|lock_until| env.ledger().timestamp() < lock_until))
+    }
+
+    /// Get several records at once; missing ids yield None in the same position
+    pub fn get_records_by_ids(env: Env, ids: Vec<u64>) -> Result<Vec<Option<ActionRecord>>, RegistryError> {
+        if ids.len() > MAX_BATCH_IDS {
+            return Err(RegistryError::BatchTooLarge);
+        }
+
+        let mut records = Vec::new(&env);
+        for id in ids.iter() {
+            records.push_back(env.storage().persistent().get(&DataKey::Record(id)));
+        }
+
+        Ok(records)
+    }
+
+    /// Get a page of a record's tx refs (empty if the record or start is out of range)
+    pub fn get_tx_refs(env: Env, id: u64, start: u32, limit: u32) -> Vec<String> {
+        let tx_refs = match Self::get_record(env.clone(), id) {
+            Ok(record) => record.tx_refs,
+            Err(_) => return Vec::new(&env),
+        };
+
+        if start >= tx_refs.len() {
+            return Vec::new(&env);
+        }
+        let end = start.saturating_add(limit).min(tx_refs.len());
+        tx_refs.slice(start..end)
+    }
+
+    /// Check whether a record exists without loading it
+    pub fn record_exists(env: Env, id: u64) -> bool {
+        env.storage().persistent().has(&DataKey::Record(id))
+    }
+
     /// Get all record IDs for a user
     pub fn get_user_records(env: Env, user: Address) -> Vec<u64> {
         env.storage()
@@ -229,6 +659,68 @@ impl StellarWizardRegistry {
             .unwrap_or(Vec::new(&env))
     }
 
+    /// Records where `addr` is the user or the counterparty, in id order (limit 0 = no cap)
+    pub fn get_records_involving(env: Env, addr: Address, limit: u32) -> Vec<ActionRecord> {
+        let as_user = Self::get_user_records(env.clone(), addr.clone());
+        let as_counterparty: Vec<u64> = env.storage()
+            .persistent()
+            .get(&DataKey::CounterpartyRecords(addr))
+            .unwrap_or(Vec::new(&env));
+
+        // Merge the two id lists, taking records where addr is on both sides once
+        let mut records = Vec::new(&env);
+        let (mut i, mut j) = (0u32, 0u32);
+        loop {
+            if limit > 0 && records.len() >= limit {
+                break;
+            }
+            let id = match (as_user.get(i), as_counterparty.get(j)) {
+                (Some(a), Some(b)) if a == b => {
+                    i += 1;
+                    j += 1;
+                    a
+                }
+                (Some(a), Some(b)) if a < b => {
+                    i += 1;
+                    a
+                }
+                (Some(a), None) => {
+                    i += 1;
+                    a
+                }
+                (_, Some(b)) => {
+                    j += 1;
+                    b
+                }
+                (None, None) => break,
+            };
+            if let Ok(record) = Self::get_record(env.clone(), id) {
+                records.push_back(record);
+            }
+        }
+
+        records
+    }
+
+    /// Get the ledger timestamp of a user's most recent action (0 if none)
+    pub fn get_user_last_activity(env: Env, user: Address) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::UserLastActivity(user))
+            .unwrap_or(0u64)
+    }
+
+    /// A user's record count, routed volume, fees paid and last activity in one call
+    pub fn get_user_dashboard(env: Env, user: Address) -> UserDashboard {
+        let storage = env.storage().persistent();
+        UserDashboard {
+            record_count: Self::get_user_records(env.clone(), user.clone()).len(),
+            total_volume: storage.get(&DataKey::UserVolume(user.clone())).unwrap_or(0i128),
+            total_fees_paid: storage.get(&DataKey::UserFeesPaid(user.clone())).unwrap_or(0i128),
+            last_activity: Self::get_user_last_activity(env.clone(), user),
+        }
+    }
+
     /// Get contract configuration
     pub fn get_config(env: &Env) -> Result<Config, RegistryError> {
         env.storage()
@@ -237,6 +729,11 @@ impl StellarWizardRegistry {
             .ok_or(RegistryError::RecordNotFound)
     }
 
+    /// Get the contract owner
+    pub fn get_owner(env: Env) -> Result<Address, RegistryError> {
+        Ok(Self::get_config(&env)?.owner)
+    }
+
     /// Update fee rate (owner only)
     pub fn set_fee_bps(env: Env, fee_bps: u32) -> Result<(), RegistryError> {
         let mut config = Self::get_config(&env)?;
@@ -246,8 +743,11 @@ impl StellarWizardRegistry {
             return Err(RegistryError::InvalidFeeRate);
         }
 
+        record_config_change(&env, &config)?;
+
         config.fee_bps = fee_bps;
         env.storage().instance().set(&DataKey::Config, &config);
+        push_config_snapshot(&env, &config);
 
         log!(&env, "Fee rate updated to {} bps", fee_bps);
 
@@ -259,14 +759,154 @@ impl StellarWizardRegistry {
         let mut config = Self::get_config(&env)?;
         config.owner.require_auth();
 
+        record_config_change(&env, &config)?;
+
         config.fee_wallet = fee_wallet.clone();
         env.storage().instance().set(&DataKey::Config, &config);
+        push_config_snapshot(&env, &config);
 
         log!(&env, "Fee wallet updated to: {:?}", fee_wallet);
 
         Ok(())
     }
 
+    /// Replace the fee wallet and emit the previous and new addresses (owner only)
+    pub fn rotate_fee_wallet(env: Env, new_wallet: Address) -> Result<(), RegistryError> {
+        let mut config = Self::get_config(&env)?;
+        config.owner.require_auth();
+
+        record_config_change(&env, &config)?;
+
+        let old_wallet = config.fee_wallet.clone();
+        config.fee_wallet = new_wallet.clone();
+        env.storage().instance().set(&DataKey::Config, &config);
+        push_config_snapshot(&env, &config);
+
+        if config.emit_events {
+            env.events().publish(
+                (config.event_prefix.clone(), Symbol::new(&env, "fee_wallet_rotated")),
+                (old_wallet, new_wallet)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Set the minimum seconds between fee rate/wallet changes (owner only).
+    /// Subject to the current cooldown itself, so it cannot be lowered to skip a wait
+    pub fn set_config_change_cooldown(env: Env, cooldown_secs: u64) -> Result<(), RegistryError> {
+        let mut config = Self::get_config(&env)?;
+        config.owner.require_auth();
+
+        record_config_change(&env, &config)?;
+
+        config.config_change_cooldown_secs = cooldown_secs;
+        env.storage().instance().set(&DataKey::Config, &config);
+        push_config_snapshot(&env, &config);
+
+        log!(&env, "Config change cooldown set to {} secs", cooldown_secs);
+
+        Ok(())
+    }
+
+    /// Set the minimum fee charged on fee-bearing actions, up to the MAX_FEE_BPS share (owner only)
+    pub fn set_min_fee(env: Env, min_fee: i128) -> Result<(), RegistryError> {
+        let mut config = Self::get_config(&env)?;
+        config.owner.require_auth();
+
+        if min_fee < 0 {
+            return Err(RegistryError::InvalidAmount);
+        }
+
+        record_config_change(&env, &config)?;
+
+        config.min_fee = min_fee;
+        env.storage().instance().set(&DataKey::Config, &config);
+        push_config_snapshot(&env, &config);
+
+        log!(&env, "Minimum fee set to {}", min_fee);
+
+        Ok(())
+    }
+
+    /// Replace the volume tier table; tiers must be in strictly ascending min_volume order (owner only)
+    pub fn set_volume_tiers(env: Env, tiers: Vec<VolumeTier>) -> Result<(), RegistryError> {
+        let config = Self::get_config(&env)?;
+        config.owner.require_auth();
+
+        if tiers.len() > MAX_VOLUME_TIERS {
+            return Err(RegistryError::BatchTooLarge);
+        }
+        let mut previous: Option<i128> = None;
+        for tier in tiers.iter() {
+            if tier.fee_bps > MAX_FEE_BPS {
+                return Err(RegistryError::InvalidFeeRate);
+            }
+            if tier.min_volume <= 0 || previous.is_some_and(|min_volume| tier.min_volume <= min_volume) {
+                return Err(RegistryError::InvalidAmount);
+            }
+            previous = Some(tier.min_volume);
+        }
+
+        record_config_change(&env, &config)?;
+
+        env.storage().instance().set(&DataKey::VolumeTiers, &tiers);
+        push_config_snapshot(&env, &config);
+
+        log!(&env, "Volume tiers set: {} tiers", tiers.len());
+
+        Ok(())
+    }
+
+    pub fn get_volume_tiers(env: Env) -> Vec<VolumeTier> {
+        env.storage().instance().get(&DataKey::VolumeTiers).unwrap_or(Vec::new(&env))
+    }
+
+    /// Fee rate for a user's next action: the highest tier their cumulative volume has
+    /// reached, or the base fee_bps below the first tier
+    pub fn get_user_fee_bps(env: Env, user: Address) -> Result<u32, RegistryError> {
+        let config = Self::get_config(&env)?;
+        let volume: i128 = env.storage()
+            .persistent()
+            .get(&DataKey::UserVolume(user))
+            .unwrap_or(0i128);
+
+        let mut fee_bps = config.fee_bps;
+        for tier in Self::get_volume_tiers(env).iter() {
+            if volume < tier.min_volume {
+                break;
+            }
+            fee_bps = tier.fee_bps;
+        }
+        Ok(fee_bps)
+    }
+
+    /// Route a share of each fee, up to MAX_TREASURY_SPLIT_BPS, to a treasury/staking contract
+    /// (owner only, None = off)
+    pub fn set_treasury_split(
+        env: Env,
+        treasury_address: Option<Address>,
+        split_bps: u32,
+    ) -> Result<(), RegistryError> {
+        let mut config = Self::get_config(&env)?;
+        config.owner.require_auth();
+
+        if split_bps > MAX_TREASURY_SPLIT_BPS || (split_bps > 0 && treasury_address.is_none()) {
+            return Err(RegistryError::InvalidFeeRate);
+        }
+
+        record_config_change(&env, &config)?;
+
+        config.treasury_address = treasury_address.clone();
+        config.treasury_split_bps = split_bps;
+        env.storage().instance().set(&DataKey::Config, &config);
+        push_config_snapshot(&env, &config);
+
+        log!(&env, "Treasury split set to {} bps for {:?}", split_bps, treasury_address);
+
+        Ok(())
+    }
+
     /// Pause contract (owner only)
     pub fn set_paused(env: Env, paused: bool) -> Result<(), RegistryError> {
         let mut config = Self::get_config(&env)?;
@@ -274,12 +914,98 @@ impl StellarWizardRegistry {
 
         config.paused = paused;
         env.storage().instance().set(&DataKey::Config, &config);
+        push_config_snapshot(&env, &config);
 
         log!(&env, "Contract paused status: {}", paused);
 
         Ok(())
     }
 
+    /// Block or unblock a single user from logging actions (owner only)
+    pub fn set_user_suspended(env: Env, user: Address, suspended: bool) -> Result<(), RegistryError> {
+        let config = Self::get_config(&env)?;
+        config.owner.require_auth();
+
+        if suspended {
+            env.storage().persistent().set(&DataKey::SuspendedUser(user.clone()), &true);
+        } else {
+            env.storage().persistent().remove(&DataKey::SuspendedUser(user.clone()));
+        }
+
+        log!(&env, "User {:?} suspended: {}", user, suspended);
+
+        Ok(())
+    }
+
+    /// Check whether a user is blocked from logging actions
+    pub fn is_user_suspended(env: Env, user: Address) -> bool {
+        env.storage().persistent().has(&DataKey::SuspendedUser(user))
+    }
+
+    /// Set the end of the commission-free grace period (owner only)
+    pub fn set_grace_until(env: Env, grace_until: u64) -> Result<(), RegistryError> {
+        let mut config = Self::get_config(&env)?;
+        config.owner.require_auth();
+
+        record_config_change(&env, &config)?;
+
+        config.grace_until = grace_until;
+        env.storage().instance().set(&DataKey::Config, &config);
+        push_config_snapshot(&env, &config);
+
+        log!(&env, "Fee grace period until: {}", grace_until);
+
+        Ok(())
+    }
+
+    /// Set the maximum amount a single action may log (owner only, None = no cap)
+    pub fn set_max_amount(env: Env, max_amount: Option<i128>) -> Result<(), RegistryError> {
+        let mut config = Self::get_config(&env)?;
+        config.owner.require_auth();
+
+        if let Some(max) = max_amount {
+            if max <= 0 {
+                return Err(RegistryError::InvalidAmount);
+            }
+        }
+
+        config.max_amount = max_amount;
+        env.storage().instance().set(&DataKey::Config, &config);
+        push_config_snapshot(&env, &config);
+
+        log!(&env, "Max amount updated: {:?}", max_amount);
+
+        Ok(())
+    }
+
+    /// Enable or disable action/fee event emission (owner only)
+    pub fn set_emit_events(env: Env, emit_events: bool) -> Result<(), RegistryError> {
+        let mut config = Self::get_config(&env)?;
+        config.owner.require_auth();
+
+        config.emit_events = emit_events;
+        env.storage().instance().set(&DataKey::Config, &config);
+        push_config_snapshot(&env, &config);
+
+        log!(&env, "Event emission set to: {}", emit_events);
+
+        Ok(())
+    }
+
+    /// Publish one action_full event carrying the entire record per action (owner only)
+    pub fn set_emit_full_event(env: Env, emit_full_event: bool) -> Result<(), RegistryError> {
+        let mut config = Self::get_config(&env)?;
+        config.owner.require_auth();
+
+        config.emit_full_event = emit_full_event;
+        env.storage().instance().set(&DataKey::Config, &config);
+        push_config_snapshot(&env, &config);
+
+        log!(&env, "Full event emission set to: {}", emit_full_event);
+
+        Ok(())
+    }
+
     /// Transfer ownership (owner only)
     pub fn transfer_ownership(env: Env, new_owner: Address) -> Result<(), RegistryError> {
         let mut config = Self::get_config(&env)?;
@@ -287,17 +1013,73 @@ impl StellarWizardRegistry {
 
         config.owner = new_owner.clone();
         env.storage().instance().set(&DataKey::Config, &config);
+        push_config_snapshot(&env, &config);
 
         log!(&env, "Ownership transferred to: {:?}", new_owner);
 
         Ok(())
     }
 
+    /// Most recent config snapshots, oldest first
+    pub fn get_config_history(env: Env) -> Vec<ConfigSnapshot> {
+        env.storage().instance().get(&DataKey::ConfigHistory).unwrap_or(Vec::new(&env))
+    }
+
+    /// Entry counts behind the contract's storage, to help estimate rent
+    pub fn storage_stats(env: Env) -> StorageStats {
+        StorageStats {
+            total_records: Self::get_total_records(&env),
+            user_count: env.storage().persistent().get(&DataKey::UserCount).unwrap_or(0u64),
+            config_history_len: Self::get_config_history(env).len(),
+        }
+    }
+
     /// Get total number of records
     pub fn get_total_records(env: &Env) -> u64 {
         env.storage().instance().get(&DataKey::NextId).unwrap_or(1u64) - 1
     }
 
+    /// Get total fees collected into the fee wallet across all actions
+    pub fn get_total_fees_collected(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::TotalFeesCollected).unwrap_or(0i128)
+    }
+
+    /// Get the total amount routed across all actions
+    pub fn get_total_volume(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::TotalVolume).unwrap_or(0i128)
+    }
+
+    /// Sum of fee_amount over records timestamped within [from_ts, to_ts]
+    pub fn fees_in_range(env: Env, from_ts: u64, to_ts: u64) -> Result<i128, RegistryError> {
+        let mut total = 0i128;
+
+        for id in 1..=Self::get_total_records(&env) {
+            if let Some(record) = env.storage().persistent().get::<DataKey, ActionRecord>(&DataKey::Record(id)) {
+                if record.timestamp >= from_ts && record.timestamp <= to_ts {
+                    total = total
+                        .checked_add(record.fee_amount)
+                        .ok_or(RegistryError::InvalidAmount)?;
+                }
+            }
+        }
+
+        Ok(total)
+    }
+
+    /// Compare expected fees collected against the fee wallet's live balance
+    pub fn reconcile(env: Env, token_address: Address) -> Result<(i128, i128), RegistryError> {
+        let expected = Self::get_total_fees_collected(env.clone());
+        let actual = Self::fee_wallet_balance(env, token_address)?;
+
+        Ok((expected, actual))
+    }
+
+    /// Current balance of the fee wallet in `token_address`
+    pub fn fee_wallet_balance(env: Env, token_address: Address) -> Result<i128, RegistryError> {
+        let config = Self::get_config(&env)?;
+        Ok(token::Client::new(&env, &token_address).balance(&config.fee_wallet))
+    }
+
     /// Get records in range (for pagination)
     pub fn get_records_range(env: Env, start: u64, limit: u32) -> Vec<ActionRecord> {
         let mut records = Vec::new(&env);
@@ -313,4 +1095,160 @@ impl StellarWizardRegistry {
 
         records
     }
-}
\ No newline at end of file
+
+    /// Get a page of records plus where the next page starts
+    pub fn get_records_page(env: Env, start: u64, limit: u32) -> RecordPage {
+        let total = Self::get_total_records(&env);
+        let end = if limit == 0 { total } else { (start + limit as u64 - 1).min(total) };
+
+        RecordPage {
+            records: Self::get_records_range(env, start, limit),
+            has_more: end < total,
+            // Past the end there is nothing to advance over
+            next_start: if end >= start { end + 1 } else { start },
+        }
+    }
+
+    /// Records with id > since_id in ascending order, at most `limit` (0 = no cap), skipping missing ids
+    pub fn get_records_since(env: Env, since_id: u64, limit: u32) -> Vec<ActionRecord> {
+        let mut records = Vec::new(&env);
+        let max_records = Self::get_total_records(&env);
+
+        for id in since_id.saturating_add(1)..=max_records {
+            if limit != 0 && records.len() >= limit {
+                break;
+            }
+            if let Some(record) = env.storage().persistent().get::<DataKey, ActionRecord>(&DataKey::Record(id)) {
+                records.push_back(record);
+            }
+        }
+
+        records
+    }
+
+    /// Get records in range in descending id order (same range semantics as get_records_range)
+    pub fn get_records_range_desc(env: Env, start: u64, limit: u32) -> Vec<ActionRecord> {
+        let mut records = Vec::new(&env);
+
+        for record in Self::get_records_range(env.clone(), start, limit).iter().rev() {
+            records.push_back(record);
+        }
+
+        records
+    }
+
+    /// Get records logged on a given network (scans ids, capped at limit)
+    pub fn get_records_by_network(env: Env, network: String, limit: u32) -> Vec<ActionRecord> {
+        let mut records = Vec::new(&env);
+        let max_records = Self::get_total_records(&env);
+
+        for id in 1..=max_records {
+            if limit != 0 && records.len() >= limit {
+                break;
+            }
+            if let Ok(record) = Self::get_record(env.clone(), id) {
+                if record.network == network {
+                    records.push_back(record);
+                }
+            }
+        }
+
+        records
+    }
+
+    /// Get records carrying a tag (limit 0 = no cap)
+    pub fn get_records_by_tag(env: Env, tag: String, limit: u32) -> Vec<ActionRecord> {
+        let mut records = Vec::new(&env);
+        let max_records = Self::get_total_records(&env);
+
+        for id in 1..=max_records {
+            if limit != 0 && records.len() >= limit {
+                break;
+            }
+            if let Ok(record) = Self::get_record(env.clone(), id) {
+                if record.tags.contains(&tag) {
+                    records.push_back(record);
+                }
+            }
+        }
+
+        records
+    }
+}
+
+// Reject a fee change made before the cooldown since the last one, then stamp this one
+fn record_config_change(env: &Env, config: &Config) -> Result<(), RegistryError> {
+    let now = env.ledger().timestamp();
+    if let Some(last_change) = env.storage().instance().get::<DataKey, u64>(&DataKey::LastConfigChange) {
+        if now < last_change.saturating_add(config.config_change_cooldown_secs) {
+            return Err(RegistryError::ConfigChangeCooldown);
+        }
+    }
+    env.storage().instance().set(&DataKey::LastConfigChange, &now);
+    Ok(())
+}
+
+fn push_config_snapshot(env: &Env, config: &Config) {
+    let mut history: Vec<ConfigSnapshot> = env.storage().instance()
+        .get(&DataKey::ConfigHistory)
+        .unwrap_or(Vec::new(env));
+    if history.len() >= MAX_CONFIG_HISTORY {
+        history.pop_front();
+    }
+    history.push_back(ConfigSnapshot {
+        config: config.clone(),
+        timestamp: env.ledger().timestamp(),
+    });
+    env.storage().instance().set(&DataKey::ConfigHistory, &history);
+}
+
+// Insert a record id into an id index, keeping it in ascending order
+fn insert_record_id(env: &Env, key: DataKey, id: u64) {
+    let mut ids: Vec<u64> = env.storage().persistent().get(&key).unwrap_or(Vec::new(env));
+    if let Err(index) = ids.binary_search(id) {
+        ids.insert(index, id);
+    }
+    env.storage().persistent().set(&key, &ids);
+}
+
+fn count_new_user(env: &Env) {
+    let user_count: u64 = env.storage().persistent().get(&DataKey::UserCount).unwrap_or(0u64);
+    env.storage().persistent().set(&DataKey::UserCount, &(user_count + 1));
+}
+
+fn add_user_totals(env: &Env, user: &Address, amount: i128, fee: i128) -> Result<(), RegistryError> {
+    let storage = env.storage().persistent();
+    let volume: i128 = storage.get(&DataKey::UserVolume(user.clone())).unwrap_or(0i128);
+    let volume = volume.checked_add(amount).ok_or(RegistryError::InvalidAmount)?;
+    storage.set(&DataKey::UserVolume(user.clone()), &volume);
+
+    let fees: i128 = storage.get(&DataKey::UserFeesPaid(user.clone())).unwrap_or(0i128);
+    let fees = fees.checked_add(fee).ok_or(RegistryError::InvalidAmount)?;
+    storage.set(&DataKey::UserFeesPaid(user.clone()), &fees);
+    Ok(())
+}
+
+// Keep the instance and a record's persistent entries alive after a write
+fn bump_record_ttl(env: &Env, record: &ActionRecord) {
+    env.storage().instance().extend_ttl(INSTANCE_TTL_EXTENSION, INSTANCE_TTL_EXTENSION);
+
+    let storage = env.storage().persistent();
+    let keys = [
+        DataKey::Record(record.id),
+        DataKey::RefundedAmount(record.id),
+        DataKey::RecipientFee(record.id),
+        DataKey::UserRecords(record.user.clone()),
+        DataKey::UserLastActivity(record.user.clone()),
+        DataKey::UserVolume(record.user.clone()),
+        DataKey::UserFeesPaid(record.user.clone()),
+        DataKey::UserCount,
+    ];
+    for key in keys.iter() {
+        if storage.has(key) {
+            storage.extend_ttl(key, RECORD_TTL_EXTENSION, RECORD_TTL_EXTENSION);
+        }
+    }
+    if let Some(counterparty) = record.counterparty.clone() {
+        storage.extend_ttl(&DataKey::CounterpartyRecords(counterparty), RECORD_TTL_EXTENSION, RECORD_TTL_EXTENSION);
+    }
+}
@@ -1,17 +1,24 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{testutils::Address as _, Address, Env, String};
+use soroban_sdk::{testutils::{storage::{Instance as _, Persistent as _}, Address as _, Events, Ledger}, Address, Env, IntoVal, String};
 
-fn create_registry_contract<'a>(env: &Env, owner: &Address, fee_wallet: &Address) -> Address {
+fn create_registry_contract(env: &Env, owner: &Address, fee_wallet: &Address) -> Address {
     let contract_id = env.register(StellarWizardRegistry, ());
     let client = StellarWizardRegistryClient::new(env, &contract_id);
     
     // Use 0% fee for tests to avoid token transfer issues
-    client.initialize(owner, &0u32, fee_wallet);
+    client.initialize(owner, &0u32, fee_wallet, &symbol_short!("sw_test"));
     contract_id
 }
 
+fn create_funded_token(env: &Env, holder: &Address, amount: i128) -> Address {
+    let token_admin = Address::generate(env);
+    let token = env.register_stellar_asset_contract_v2(token_admin).address();
+    token::StellarAssetClient::new(env, &token).mint(holder, &amount);
+    token
+}
+
 #[test]
 fn test_initialize() {
     let env = Env::default();
@@ -24,13 +31,14 @@ fn test_initialize() {
     let client = StellarWizardRegistryClient::new(&env, &contract_id);
 
     // Test successful initialization
-    client.initialize(&owner, &0u32, &fee_wallet);
+    client.initialize(&owner, &0u32, &fee_wallet, &symbol_short!("sw_test"));
     
     let config = client.get_config();
     assert_eq!(config.owner, owner);
     assert_eq!(config.fee_bps, 0u32);
     assert_eq!(config.fee_wallet, fee_wallet);
-    assert_eq!(config.paused, false);
+    assert!(!config.paused);
+    assert!(config.emit_events);
 }
 
 #[test]
@@ -45,9 +53,9 @@ fn test_initialize_twice() {
     let contract_id = env.register(StellarWizardRegistry, ());
     let client = StellarWizardRegistryClient::new(&env, &contract_id);
 
-    client.initialize(&owner, &0u32, &fee_wallet);
+    client.initialize(&owner, &0u32, &fee_wallet, &symbol_short!("sw_test"));
     // This should panic
-    client.initialize(&owner, &0u32, &fee_wallet);
+    client.initialize(&owner, &0u32, &fee_wallet, &symbol_short!("sw_test"));
 }
 
 #[test]
@@ -71,6 +79,9 @@ fn test_log_and_route() {
         &String::from_str(&env, "testnet"),
         &10000i128,
         &token,
+        &None,
+        &Vec::new(&env),
+        &None,
     );
 
     assert_eq!(action_id, 1u64);
@@ -111,6 +122,9 @@ fn test_append_tx_ref() {
         &String::from_str(&env, "testnet"),
         &5000i128,
         &token,
+        &None,
+        &Vec::new(&env),
+        &None,
     );
 
     // Add transaction reference
@@ -150,6 +164,9 @@ fn test_unauthorized_append_tx_ref() {
         &String::from_str(&env, "testnet"),
         &1000i128,
         &token,
+        &None,
+        &Vec::new(&env),
+        &None,
     );
 
     // Try to append tx ref with different user - should fail
@@ -213,7 +230,7 @@ fn test_pause_functionality() {
     client.set_paused(&true);
     
     let config = client.get_config();
-    assert_eq!(config.paused, true);
+    assert!(config.paused);
 
     // Try to log action while paused - should fail
     let result = client.try_log_and_route(
@@ -224,6 +241,9 @@ fn test_pause_functionality() {
         &String::from_str(&env, "testnet"),
         &1000i128,
         &token,
+        &None,
+        &Vec::new(&env),
+        &None,
     );
     assert_eq!(result, Err(Ok(RegistryError::ContractPaused)));
 
@@ -237,6 +257,9 @@ fn test_pause_functionality() {
         &String::from_str(&env, "testnet"),
         &1000i128,
         &token,
+        &None,
+        &Vec::new(&env),
+        &None,
     );
     assert_eq!(action_id, 1u64);
 }
@@ -282,6 +305,9 @@ fn test_get_records_range() {
         &String::from_str(&env, "testnet"),
         &1000i128,
         &token,
+        &None,
+        &Vec::new(&env),
+        &None,
     );
     client.log_and_route(
         &user,
@@ -291,6 +317,9 @@ fn test_get_records_range() {
         &String::from_str(&env, "testnet"),
         &2000i128,
         &token,
+        &None,
+        &Vec::new(&env),
+        &None,
     );
     client.log_and_route(
         &user,
@@ -300,6 +329,9 @@ fn test_get_records_range() {
         &String::from_str(&env, "testnet"),
         &3000i128,
         &token,
+        &None,
+        &Vec::new(&env),
+        &None,
     );
     client.log_and_route(
         &user,
@@ -309,6 +341,9 @@ fn test_get_records_range() {
         &String::from_str(&env, "testnet"),
         &4000i128,
         &token,
+        &None,
+        &Vec::new(&env),
+        &None,
     );
     client.log_and_route(
         &user,
@@ -318,6 +353,9 @@ fn test_get_records_range() {
         &String::from_str(&env, "testnet"),
         &5000i128,
         &token,
+        &None,
+        &Vec::new(&env),
+        &None,
     );
 
     // Test range query
@@ -354,6 +392,1698 @@ fn test_zero_amount_rejected() {
         &String::from_str(&env, "testnet"),
         &0i128,
         &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(result, Err(Ok(RegistryError::InvalidAmount)));
+}
+#[test]
+fn test_emit_events_toggle() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    // Events are emitted by default
+    client.log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, "hash_1"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &1000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(env.events().all().len(), 1);
+
+    // Disable events
+    client.set_emit_events(&false);
+    assert!(!client.get_config().emit_events);
+
+    let action_id = client.log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, "hash_2"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &2000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(env.events().all().len(), 0);
+
+    // Record is still written
+    let record = client.get_record(&action_id);
+    assert_eq!(record.id, 2u64);
+    assert_eq!(record.total_amount, 2000i128);
+    assert_eq!(client.get_user_records(&user).len(), 2);
+}
+
+#[test]
+fn test_reconcile_fee_wallet() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    // Seed a mock token balance for the user
+    let token_admin = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(token_admin).address();
+    token::StellarAssetClient::new(&env, &token).mint(&user, &100_000i128);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.set_fee_bps(&500u32); // 5%
+
+    client.log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, "hash_1"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &10_000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+    client.log_and_route(
+        &user,
+        &ActionType::DEFI,
+        &String::from_str(&env, "hash_2"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &20_000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+
+    let (expected, actual) = client.reconcile(&token);
+    assert_eq!(expected, 1_500i128);
+    assert_eq!(actual, 1_500i128);
+    assert_eq!(client.get_total_fees_collected(), 1_500i128);
+}
+
+#[test]
+fn test_get_records_by_network() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    for network in ["testnet", "mainnet", "testnet", "mainnet", "testnet"] {
+        client.log_and_route(
+            &user,
+            &ActionType::NFT,
+            &String::from_str(&env, "test_hash"),
+            &String::from_str(&env, "payload_ref"),
+            &String::from_str(&env, network),
+            &1000i128,
+            &token,
+            &None,
+            &Vec::new(&env),
+            &None,
+        );
+    }
+
+    // Only mainnet records are returned
+    let mainnet = client.get_records_by_network(&String::from_str(&env, "mainnet"), &10u32);
+    assert_eq!(mainnet.len(), 2);
+    assert_eq!(mainnet.get(0).unwrap().id, 2u64);
+    assert_eq!(mainnet.get(1).unwrap().id, 4u64);
+
+    // Results are capped at limit
+    let testnet = client.get_records_by_network(&String::from_str(&env, "testnet"), &2u32);
+    assert_eq!(testnet.len(), 2);
+    assert_eq!(testnet.get(0).unwrap().id, 1u64);
+    assert_eq!(testnet.get(1).unwrap().id, 3u64);
+}
+
+#[test]
+fn test_get_records_range_desc() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    for amount in [1000i128, 2000, 3000, 4000, 5000] {
+        client.log_and_route(
+            &user,
+            &ActionType::NFT,
+            &String::from_str(&env, "test_hash"),
+            &String::from_str(&env, "payload_ref"),
+            &String::from_str(&env, "testnet"),
+            &amount,
+            &token,
+            &None,
+            &Vec::new(&env),
+            &None,
+        );
+    }
+
+    // Same id set as the ascending query, reversed
+    let ascending = client.get_records_range(&2u64, &3u32);
+    let descending = client.get_records_range_desc(&2u64, &3u32);
+    assert_eq!(descending.len(), 3);
+    assert_eq!(descending.get(0).unwrap().id, 4u64);
+    assert_eq!(descending.get(1).unwrap().id, 3u64);
+    assert_eq!(descending.get(2).unwrap().id, 2u64);
+    for i in 0..ascending.len() {
+        assert_eq!(descending.get(i).unwrap(), ascending.get(ascending.len() - 1 - i).unwrap());
+    }
+
+    // Limit is preserved at the end of the range
+    let tail = client.get_records_range_desc(&4u64, &10u32);
+    assert_eq!(tail.len(), 2);
+    assert_eq!(tail.get(0).unwrap().id, 5u64);
+}
+
+#[test]
+fn test_record_exists() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    for _ in 0..2 {
+        client.log_and_route(
+            &user,
+            &ActionType::NFT,
+            &String::from_str(&env, "test_hash"),
+            &String::from_str(&env, "payload_ref"),
+            &String::from_str(&env, "testnet"),
+            &1000i128,
+            &token,
+            &None,
+            &Vec::new(&env),
+            &None,
+        );
+    }
+
+    // Existing and out-of-range ids
+    assert!(client.record_exists(&1u64));
+    assert!(!client.record_exists(&0u64));
+    assert!(!client.record_exists(&3u64));
+
+    // A removed record no longer exists even though its id is in range
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().remove(&DataKey::Record(2u64));
+    });
+    assert!(!client.record_exists(&2u64));
+}
+
+#[test]
+fn test_fee_recipient_override() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let partner_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+
+    let token_admin = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(token_admin).address();
+    token::StellarAssetClient::new(&env, &token).mint(&user, &100_000i128);
+    let token_client = token::Client::new(&env, &token);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.set_fee_bps(&500u32); // 5%
+
+    // Overridden action pays the designated wallet
+    let overridden_id = client.log_and_route(
+        &user,
+        &ActionType::DEFI,
+        &String::from_str(&env, "hash_1"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &10_000i128,
+        &token,
+        &Some(partner_wallet.clone()),
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(token_client.balance(&partner_wallet), 500i128);
+    assert_eq!(token_client.balance(&fee_wallet), 0i128);
+    assert_eq!(client.get_record(&overridden_id).fee_recipient_override, Some(partner_wallet.clone()));
+
+    // Normal action uses the default fee wallet
+    let normal_id = client.log_and_route(
+        &user,
+        &ActionType::DEFI,
+        &String::from_str(&env, "hash_2"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &10_000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(token_client.balance(&fee_wallet), 500i128);
+    assert_eq!(token_client.balance(&partner_wallet), 500i128);
+    assert_eq!(client.get_record(&normal_id).fee_recipient_override, None);
+}
+
+#[test]
+fn test_fee_grace_period() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = create_funded_token(&env, &user, 100_000i128);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.set_fee_bps(&500u32); // 5%
+    client.set_grace_until(&1_000u64);
+
+    // During the grace period no fee is charged
+    env.ledger().set_timestamp(999);
+    let grace_id = client.log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, "hash_1"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &10_000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(client.get_record(&grace_id).fee_amount, 0i128);
+
+    // Afterwards the normal fee applies
+    env.ledger().set_timestamp(1_000);
+    let paid_id = client.log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, "hash_2"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &10_000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(client.get_record(&paid_id).fee_amount, 500i128);
+    assert_eq!(token::Client::new(&env, &token).balance(&fee_wallet), 500i128);
+}
+
+#[test]
+fn test_min_fee_floor() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = create_funded_token(&env, &user, 100_000i128);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.set_fee_bps(&200u32); // 2%
+    client.set_min_fee(&50i128);
+    assert_eq!(client.try_set_min_fee(&-1i128), Err(Ok(RegistryError::InvalidAmount)));
+
+    let log = |plan_hash: &str, amount: i128| client.log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, plan_hash),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &amount,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+
+    // 2% of 1000 is 20, floored up to the minimum
+    let small_id = log("hash_1", 1000);
+    assert_eq!(client.get_record(&small_id).fee_amount, 50i128);
+
+    // The floor never charges more than the 10% cap: 10 on 100
+    let tiny_id = log("hash_3", 100);
+    assert_eq!(client.get_record(&tiny_id).fee_amount, 10i128);
+
+    // 2% of 10_000 is already above the floor
+    let large_id = log("hash_2", 10_000);
+    assert_eq!(client.get_record(&large_id).fee_amount, 200i128);
+    assert_eq!(token::Client::new(&env, &token).balance(&fee_wallet), 260i128);
+
+    // The floor is a fee change like any other
+    client.set_config_change_cooldown(&3600);
+    env.ledger().set_timestamp(10);
+    assert_eq!(client.try_set_min_fee(&10i128), Err(Ok(RegistryError::ConfigChangeCooldown)));
+}
+
+#[test]
+fn test_max_amount() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.set_max_amount(&Some(5000i128));
+
+    // Exactly at the cap is accepted
+    let action_id = client.log_and_route(
+        &user,
+        &ActionType::DEFI,
+        &String::from_str(&env, "test_hash"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &5000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(action_id, 1u64);
+
+    // Above the cap is rejected
+    let result = client.try_log_and_route(
+        &user,
+        &ActionType::DEFI,
+        &String::from_str(&env, "test_hash"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &5001i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
     );
     assert_eq!(result, Err(Ok(RegistryError::InvalidAmount)));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_user_last_activity() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    // No actions yet
+    assert_eq!(client.get_user_last_activity(&user), 0);
+
+    for timestamp in [1_700_000_000u64, 1_700_003_600u64] {
+        env.ledger().set_timestamp(timestamp);
+        client.log_and_route(
+            &user,
+            &ActionType::NFT,
+            &String::from_str(&env, "test_hash"),
+            &String::from_str(&env, "payload_ref"),
+            &String::from_str(&env, "testnet"),
+            &1000i128,
+            &token,
+            &None,
+            &Vec::new(&env),
+            &None,
+        );
+    }
+
+    assert_eq!(client.get_user_last_activity(&user), 1_700_003_600);
+}
+
+#[test]
+fn test_get_owner() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    // Uninitialized contract has no owner
+    let uninitialized = env.register(StellarWizardRegistry, ());
+    let client = StellarWizardRegistryClient::new(&env, &uninitialized);
+    assert_eq!(client.try_get_owner(), Err(Ok(RegistryError::RecordNotFound)));
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    assert_eq!(client.get_owner(), owner);
+}
+
+#[test]
+fn test_total_volume() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    assert_eq!(client.get_total_volume(), 0);
+
+    for amount in [1000i128, 2500i128, 750i128] {
+        client.log_and_route(
+            &user,
+            &ActionType::DEFI,
+            &String::from_str(&env, "test_hash"),
+            &String::from_str(&env, "payload_ref"),
+            &String::from_str(&env, "testnet"),
+            &amount,
+            &token,
+            &None,
+            &Vec::new(&env),
+            &None,
+        );
+    }
+
+    assert_eq!(client.get_total_volume(), 4250);
+}
+
+#[test]
+fn test_event_prefix() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = create_funded_token(&env, &user, 10_000);
+
+    let contract_id = env.register(StellarWizardRegistry, ());
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.initialize(&owner, &250u32, &fee_wallet, &symbol_short!("mainnet"));
+    assert_eq!(client.get_config().event_prefix, symbol_short!("mainnet"));
+
+    client.log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, "test_hash"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &1000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+
+    // fee_paid and action are the last two events and both lead with the prefix
+    let events = env.events().all();
+    let (fee_contract, fee_topics, _) = events.get(events.len() - 2).unwrap();
+    let (action_contract, action_topics, _) = events.get(events.len() - 1).unwrap();
+    assert_eq!(fee_contract, contract_id);
+    assert_eq!(action_contract, contract_id);
+    assert_eq!(fee_topics, (symbol_short!("mainnet"), symbol_short!("fee_paid")).into_val(&env));
+    assert_eq!(action_topics, (symbol_short!("mainnet"), symbol_short!("action")).into_val(&env));
+}
+
+#[test]
+fn test_refund_partial() {
+    let env = Env::default();
+    // The fee wallet authorizes the refund transfer as a non-root signer
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = create_funded_token(&env, &user, 100_000);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.set_fee_bps(&500u32); // 5%
+
+    let id = client.log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, "test_hash"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &10_000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(client.get_record(&id).fee_amount, 500);
+
+    let token_client = token::Client::new(&env, &token);
+
+    // Two partial refunds that together equal the fee
+    client.refund_partial(&id, &200, &token);
+    assert_eq!(client.get_refunded_amount(&id), 200);
+    client.refund_partial(&id, &300, &token);
+    assert_eq!(client.get_refunded_amount(&id), 500);
+    assert_eq!(token_client.balance(&fee_wallet), 0);
+    assert_eq!(token_client.balance(&user), 100_000);
+    assert_eq!(client.get_total_fees_collected(), 0);
+
+    // Anything further would over-refund
+    assert_eq!(
+        client.try_refund_partial(&id, &1, &token),
+        Err(Ok(RegistryError::RefundExceedsFee))
+    );
+    assert_eq!(client.get_refunded_amount(&id), 500);
+}
+
+#[test]
+fn test_import_record() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    let mut tx_refs = Vec::new(&env);
+    tx_refs.push_back(String::from_str(&env, "legacy_tx"));
+    let record = ActionRecord {
+        id: 42,
+        user: user.clone(),
+        action_type: ActionType::DEFI,
+        plan_hash: String::from_str(&env, "legacy_hash"),
+        payload_ref: String::from_str(&env, "legacy_payload"),
+        timestamp: 1_600_000_000,
+        network: String::from_str(&env, "mainnet"),
+        tx_refs,
+        fee_amount: 25,
+        total_amount: 1000,
+        fee_recipient_override: None,
+        tags: Vec::new(&env),
+        counterparty: None,
+        lock_until: None,
+    };
+
+    client.import_record(&record);
+
+    // Only an imported event is emitted
+    let events = env.events().all();
+    let (_, topics, _) = events.get(events.len() - 1).unwrap();
+    assert_eq!(topics, (symbol_short!("sw_test"), symbol_short!("imported")).into_val(&env));
+    assert_eq!(events.len(), 1);
+
+    // Reads back intact and is indexed
+    assert_eq!(client.get_record(&42u64), record);
+    assert_eq!(client.get_user_records(&user), Vec::from_array(&env, [42u64]));
+    assert_eq!(client.get_user_last_activity(&user), 1_600_000_000);
+    assert_eq!(client.get_total_fees_collected(), 0);
+
+    // Colliding ids are rejected
+    assert_eq!(client.try_import_record(&record), Err(Ok(RegistryError::RecordAlreadyExists)));
+
+    // New actions are numbered after the imported record
+    let id = client.log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, "test_hash"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &1000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(id, 43u64);
+
+    // An older record slots into the indexes in id order
+    let counterparty = Address::generate(&env);
+    let mut older = record.clone();
+    older.id = 7;
+    older.counterparty = Some(counterparty.clone());
+    client.import_record(&older);
+    assert_eq!(client.get_user_records(&user), Vec::from_array(&env, [7u64, 42u64, 43u64]));
+    let mut involving = Vec::new(&env);
+    for record in client.get_records_involving(&user, &0).iter() {
+        involving.push_back(record.id);
+    }
+    assert_eq!(involving, Vec::from_array(&env, [7u64, 42u64, 43u64]));
+    assert_eq!(client.get_records_involving(&counterparty, &0).len(), 1);
+
+    // Numbering is unaffected by the lower id
+    assert_eq!(client.get_total_records(), 43u64);
+
+    // An id with no successor is rejected rather than wrapping the counter
+    let mut last = record.clone();
+    last.id = u64::MAX;
+    assert_eq!(client.try_import_record(&last), Err(Ok(RegistryError::InvalidAmount)));
+}
+
+#[test]
+fn test_records_by_tag() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    let swap = String::from_str(&env, "swap:xlm-usdc");
+    let drop = String::from_str(&env, "drop:genesis");
+    let tag_sets = [
+        Vec::from_array(&env, [swap.clone()]),
+        Vec::from_array(&env, [drop.clone()]),
+        Vec::from_array(&env, [swap.clone(), drop.clone()]),
+        Vec::new(&env),
+    ];
+    for tags in tag_sets.iter() {
+        client.log_and_route(
+            &user,
+            &ActionType::DEFI,
+            &String::from_str(&env, "test_hash"),
+            &String::from_str(&env, "payload_ref"),
+            &String::from_str(&env, "testnet"),
+            &1000i128,
+            &token,
+            &None,
+            tags,
+            &None,
+        );
+    }
+
+    let swaps = client.get_records_by_tag(&swap, &0);
+    assert_eq!(swaps.len(), 2);
+    assert_eq!(swaps.get(0).unwrap().id, 1u64);
+    assert_eq!(swaps.get(1).unwrap().id, 3u64);
+    assert_eq!(client.get_record(&3u64).tags, tag_sets[2]);
+
+    // Limit caps the results
+    assert_eq!(client.get_records_by_tag(&drop, &1).len(), 1);
+    assert_eq!(client.get_records_by_tag(&String::from_str(&env, "none"), &0).len(), 0);
+
+    // Too many tags or an overlong tag is rejected
+    let too_many = Vec::from_array(&env, [
+        swap.clone(), swap.clone(), swap.clone(), swap.clone(), swap.clone(), swap.clone(),
+    ]);
+    let overlong = Vec::from_array(&env, [String::from_str(&env, "a-tag-that-is-much-longer-than-32-chars")]);
+    for tags in [too_many, overlong].iter() {
+        let result = client.try_log_and_route(
+            &user,
+            &ActionType::DEFI,
+            &String::from_str(&env, "test_hash"),
+            &String::from_str(&env, "payload_ref"),
+            &String::from_str(&env, "testnet"),
+            &1000i128,
+            &token,
+            &None,
+            tags,
+            &None,
+        );
+        assert_eq!(result, Err(Ok(RegistryError::InvalidTags)));
+    }
+}
+
+#[test]
+fn test_get_tx_refs_paged() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    let id = client.log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, "test_hash"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &1000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+
+    let refs = ["tx_1", "tx_2", "tx_3", "tx_4", "tx_5"];
+    for tx_ref in refs.iter() {
+        client.append_tx_ref(&user, &id, &String::from_str(&env, tx_ref));
+    }
+
+    let page = client.get_tx_refs(&id, &0, &2);
+    assert_eq!(page, Vec::from_array(&env, [String::from_str(&env, "tx_1"), String::from_str(&env, "tx_2")]));
+    let page = client.get_tx_refs(&id, &2, &2);
+    assert_eq!(page, Vec::from_array(&env, [String::from_str(&env, "tx_3"), String::from_str(&env, "tx_4")]));
+
+    // Last page is short
+    let page = client.get_tx_refs(&id, &4, &2);
+    assert_eq!(page, Vec::from_array(&env, [String::from_str(&env, "tx_5")]));
+
+    // Out-of-range start and unknown record are empty
+    assert_eq!(client.get_tx_refs(&id, &5, &2).len(), 0);
+    assert_eq!(client.get_tx_refs(&99u64, &0, &2).len(), 0);
+}
+
+#[test]
+fn test_config_change_cooldown() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let new_fee_wallet = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    client.set_config_change_cooldown(&3600);
+    assert_eq!(client.get_config().config_change_cooldown_secs, 3600);
+
+    env.ledger().set_timestamp(10_000);
+    client.set_fee_bps(&100);
+
+    // Too soon for either fee setter
+    env.ledger().set_timestamp(12_000);
+    assert_eq!(client.try_set_fee_bps(&200), Err(Ok(RegistryError::ConfigChangeCooldown)));
+    assert_eq!(client.try_set_fee_wallet(&new_fee_wallet), Err(Ok(RegistryError::ConfigChangeCooldown)));
+    assert_eq!(client.get_config().fee_bps, 100);
+
+    // Diverting fees to a treasury or waiving them counts as a change too
+    assert_eq!(
+        client.try_set_treasury_split(&Some(new_fee_wallet.clone()), &5000u32),
+        Err(Ok(RegistryError::ConfigChangeCooldown))
+    );
+    assert_eq!(client.try_set_grace_until(&20_000u64), Err(Ok(RegistryError::ConfigChangeCooldown)));
+
+    // Nor can the cooldown be dropped to skip the wait
+    assert_eq!(client.try_set_config_change_cooldown(&0), Err(Ok(RegistryError::ConfigChangeCooldown)));
+    assert_eq!(client.get_config().config_change_cooldown_secs, 3600);
+
+    // Once the cooldown has elapsed the change goes through
+    env.ledger().set_timestamp(13_600);
+    client.set_fee_bps(&200);
+    assert_eq!(client.get_config().fee_bps, 200);
+}
+
+#[test]
+fn test_config_history() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    assert_eq!(client.get_config_history().len(), 0);
+
+    // Twelve changes overflow the ten-entry buffer
+    for i in 1..=12u32 {
+        env.ledger().set_timestamp(i as u64 * 100);
+        client.set_fee_bps(&i);
+    }
+
+    let history = client.get_config_history();
+    assert_eq!(history.len(), 10);
+    for (i, snapshot) in history.iter().enumerate() {
+        let fee_bps = i as u32 + 3;
+        assert_eq!(snapshot.config.fee_bps, fee_bps);
+        assert_eq!(snapshot.timestamp, fee_bps as u64 * 100);
+    }
+
+    // Other owner setters are recorded too
+    client.set_paused(&true);
+    let history = client.get_config_history();
+    assert_eq!(history.len(), 10);
+    assert!(history.get(9).unwrap().config.paused);
+    assert_eq!(history.get(0).unwrap().config.fee_bps, 4);
+}
+
+#[test]
+fn test_get_records_by_ids() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    for plan_hash in ["hash_1", "hash_2"] {
+        client.log_and_route(
+            &user,
+            &ActionType::NFT,
+            &String::from_str(&env, plan_hash),
+            &String::from_str(&env, "payload_ref"),
+            &String::from_str(&env, "testnet"),
+            &1000i128,
+            &token,
+            &None,
+            &Vec::new(&env),
+            &None,
+        );
+    }
+
+    let records = client.get_records_by_ids(&Vec::from_array(&env, [2u64, 99u64, 1u64, 0u64]));
+    assert_eq!(records.len(), 4);
+    assert_eq!(records.get(0).unwrap().unwrap().plan_hash, String::from_str(&env, "hash_2"));
+    assert_eq!(records.get(1).unwrap(), None);
+    assert_eq!(records.get(2).unwrap().unwrap().plan_hash, String::from_str(&env, "hash_1"));
+    assert_eq!(records.get(3).unwrap(), None);
+
+    // Input length is bounded
+    let mut too_many = Vec::new(&env);
+    for id in 0..51u64 {
+        too_many.push_back(id);
+    }
+    assert_eq!(client.try_get_records_by_ids(&too_many), Err(Ok(RegistryError::BatchTooLarge)));
+}
+
+#[test]
+fn test_user_suspension() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let other_user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    client.set_user_suspended(&user, &true);
+    assert!(client.is_user_suspended(&user));
+    assert!(!client.is_user_suspended(&other_user));
+
+    let result = client.try_log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, "hash_1"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &1000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(result, Err(Ok(RegistryError::UserSuspended)));
+    assert_eq!(client.get_total_records(), 0);
+
+    // Other users are unaffected
+    client.log_and_route(
+        &other_user,
+        &ActionType::NFT,
+        &String::from_str(&env, "hash_2"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &1000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+
+    // Lifting the suspension lets the user log again
+    client.set_user_suspended(&user, &false);
+    assert!(!client.is_user_suspended(&user));
+    let id = client.log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, "hash_3"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &1000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+    assert_eq!(client.get_record(&id).user, user);
+}
+
+#[test]
+fn test_treasury_fee_split() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = create_funded_token(&env, &user, 100_000i128);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.set_fee_bps(&500u32); // 5%
+
+    // A split without a treasury is rejected
+    assert_eq!(client.try_set_treasury_split(&None, &3000u32), Err(Ok(RegistryError::InvalidFeeRate)));
+    assert_eq!(client.try_set_treasury_split(&Some(treasury.clone()), &5001u32), Err(Ok(RegistryError::InvalidFeeRate)));
+    client.set_treasury_split(&Some(treasury.clone()), &3000u32); // 30% of fees
+
+    let id = client.log_and_route(
+        &user,
+        &ActionType::DEFI,
+        &String::from_str(&env, "hash_1"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &10_000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+
+    // One event per leg of the split, ahead of the action event
+    let mut registry_events = Vec::new(&env);
+    for (emitter, topics, _) in env.events().all().iter() {
+        if emitter == contract_id {
+            registry_events.push_back(topics);
+        }
+    }
+    assert_eq!(registry_events, Vec::from_array(&env, [
+        (symbol_short!("sw_test"), symbol_short!("fee_paid")).into_val(&env),
+        (symbol_short!("sw_test"), symbol_short!("treasury")).into_val(&env),
+        (symbol_short!("sw_test"), symbol_short!("action")).into_val(&env),
+    ]));
+
+    // 500 fee: 150 to the treasury, 350 to the fee wallet
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(client.get_record(&id).fee_amount, 500i128);
+    assert_eq!(token_client.balance(&treasury), 150i128);
+    assert_eq!(token_client.balance(&fee_wallet), 350i128);
+    assert_eq!(client.get_total_fees_collected(), 350i128);
+}
+
+#[test]
+fn test_refund_partial_capped_at_recipient_share() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = create_funded_token(&env, &user, 100_000i128);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.set_fee_bps(&500u32); // 5%
+    client.set_treasury_split(&Some(treasury.clone()), &3000u32); // 30% of fees
+
+    let id = client.log_and_route(
+        &user,
+        &ActionType::DEFI,
+        &String::from_str(&env, "hash_1"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &10_000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+
+    // Only the fee wallet's 350 can be refunded, not the treasury's 150
+    assert_eq!(
+        client.try_refund_partial(&id, &500, &token),
+        Err(Ok(RegistryError::RefundExceedsFee))
+    );
+    client.refund_partial(&id, &350, &token);
+
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&fee_wallet), 0);
+    assert_eq!(token_client.balance(&treasury), 150i128);
+    assert_eq!(client.get_total_fees_collected(), 0);
+    assert_eq!(
+        client.try_refund_partial(&id, &1, &token),
+        Err(Ok(RegistryError::RefundExceedsFee))
+    );
+}
+
+#[test]
+fn test_get_records_page() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    for plan_hash in ["hash_1", "hash_2", "hash_3", "hash_4", "hash_5"] {
+        client.log_and_route(
+            &user,
+            &ActionType::NFT,
+            &String::from_str(&env, plan_hash),
+            &String::from_str(&env, "payload_ref"),
+            &String::from_str(&env, "testnet"),
+            &1000i128,
+            &token,
+            &None,
+            &Vec::new(&env),
+            &None,
+        );
+    }
+
+    let first = client.get_records_page(&1u64, &2u32);
+    assert_eq!(first.records.len(), 2);
+    assert!(first.has_more);
+    assert_eq!(first.next_start, 3u64);
+
+    let second = client.get_records_page(&first.next_start, &2u32);
+    assert_eq!(second.records.get(0).unwrap().id, 3u64);
+    assert!(second.has_more);
+
+    // Final page is short and reports nothing left
+    let last = client.get_records_page(&second.next_start, &2u32);
+    assert_eq!(last.records.len(), 1);
+    assert_eq!(last.records.get(0).unwrap().id, 5u64);
+    assert!(!last.has_more);
+    assert_eq!(last.next_start, 6u64);
+
+    let beyond = client.get_records_page(&last.next_start, &2u32);
+    assert_eq!(beyond.records.len(), 0);
+    assert!(!beyond.has_more);
+}
+
+#[test]
+fn test_fee_wallet_balance() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    let token = create_funded_token(&env, &fee_wallet, 12_345i128);
+    assert_eq!(client.fee_wallet_balance(&token), 12_345i128);
+
+    // Follows the configured wallet
+    let new_fee_wallet = Address::generate(&env);
+    client.set_fee_wallet(&new_fee_wallet);
+    assert_eq!(client.fee_wallet_balance(&token), 0i128);
+}
+
+#[test]
+fn test_get_records_involving() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    // 1: alice alone, 2: bob with alice as counterparty, 3: alice with bob as counterparty
+    let actions = [
+        (alice.clone(), None),
+        (bob.clone(), Some(alice.clone())),
+        (alice.clone(), Some(bob.clone())),
+    ];
+    for (user, counterparty) in actions.iter() {
+        client.log_and_route(
+            user,
+            &ActionType::DEFI,
+            &String::from_str(&env, "swap_hash"),
+            &String::from_str(&env, "payload_ref"),
+            &String::from_str(&env, "testnet"),
+            &1000i128,
+            &token,
+            &None,
+            &Vec::new(&env),
+            counterparty,
+        );
+    }
+    assert_eq!(client.get_record(&2).counterparty, Some(alice.clone()));
+
+    let ids = |addr: &Address, limit: u32| {
+        let mut ids = Vec::new(&env);
+        for record in client.get_records_involving(addr, &limit).iter() {
+            ids.push_back(record.id);
+        }
+        ids
+    };
+
+    // Both parties find the shared actions, in id order
+    assert_eq!(ids(&alice, 0), Vec::from_array(&env, [1u64, 2u64, 3u64]));
+    assert_eq!(ids(&bob, 0), Vec::from_array(&env, [2u64, 3u64]));
+    assert_eq!(ids(&alice, 2), Vec::from_array(&env, [1u64, 2u64]));
+
+    // An action naming the same address on both sides appears once
+    client.log_and_route(
+        &bob,
+        &ActionType::DEFI,
+        &String::from_str(&env, "self_hash"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &1000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &Some(bob.clone()),
+    );
+    assert_eq!(ids(&bob, 0), Vec::from_array(&env, [2u64, 3u64, 4u64]));
+}
+
+#[test]
+fn test_storage_stats() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    assert_eq!(client.storage_stats(), StorageStats { total_records: 0, user_count: 0, config_history_len: 0 });
+
+    for (user, counterparty) in [(&alice, None), (&bob, None), (&alice, Some(bob.clone()))] {
+        client.log_and_route(
+            user,
+            &ActionType::NFT,
+            &String::from_str(&env, "hash"),
+            &String::from_str(&env, "payload_ref"),
+            &String::from_str(&env, "testnet"),
+            &1000i128,
+            &token,
+            &None,
+            &Vec::new(&env),
+            &counterparty,
+        );
+    }
+    client.set_fee_bps(&100u32);
+
+    assert_eq!(client.storage_stats(), StorageStats { total_records: 3, user_count: 2, config_history_len: 1 });
+
+    // Writes extend the TTL of the instance and the touched record's entries
+    env.as_contract(&contract_id, || {
+        assert!(env.storage().instance().get_ttl() >= INSTANCE_TTL_EXTENSION);
+        assert!(env.storage().persistent().get_ttl(&DataKey::Record(3)) >= RECORD_TTL_EXTENSION);
+        assert!(env.storage().persistent().get_ttl(&DataKey::UserRecords(alice.clone())) >= RECORD_TTL_EXTENSION);
+        assert!(env.storage().persistent().get_ttl(&DataKey::CounterpartyRecords(bob.clone())) >= RECORD_TTL_EXTENSION);
+    });
+}
+
+#[test]
+fn test_get_user_dashboard() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = create_funded_token(&env, &user, 100_000i128);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.set_fee_bps(&500u32); // 5%
+
+    let empty = client.get_user_dashboard(&user);
+    assert_eq!(empty.record_count, 0);
+    assert_eq!(empty.total_volume, 0i128);
+    assert_eq!(empty.last_activity, 0);
+
+    env.ledger().set_timestamp(100);
+    client.log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, "hash_1"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &1_000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+
+    env.ledger().set_timestamp(250);
+    client.log_and_route(
+        &user,
+        &ActionType::DEFI,
+        &String::from_str(&env, "hash_2"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &4_000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+
+    let dashboard = client.get_user_dashboard(&user);
+    assert_eq!(dashboard.record_count, 2);
+    assert_eq!(dashboard.total_volume, 5_000i128);
+    assert_eq!(dashboard.total_fees_paid, 250i128);
+    assert_eq!(dashboard.last_activity, 250);
+}
+
+#[test]
+fn test_rotate_fee_wallet() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let new_wallet = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    client.rotate_fee_wallet(&new_wallet);
+    // The event carries both the prior and the new wallet
+    let (emitter, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(emitter, contract_id);
+    assert_eq!(
+        topics,
+        (symbol_short!("sw_test"), Symbol::new(&env, "fee_wallet_rotated")).into_val(&env)
+    );
+    let (old, new): (Address, Address) = data.into_val(&env);
+    assert_eq!(old, fee_wallet);
+    assert_eq!(new, new_wallet);
+
+    // The rotation is recorded like any other config change
+    assert_eq!(client.get_config().fee_wallet, new_wallet);
+    let history = client.get_config_history();
+    assert_eq!(history.last().unwrap().config.fee_wallet, new_wallet);
+}
+
+#[test]
+fn test_get_records_since() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    for plan_hash in ["hash_1", "hash_2", "hash_3", "hash_4", "hash_5"] {
+        client.log_and_route(
+            &user,
+            &ActionType::NFT,
+            &String::from_str(&env, plan_hash),
+            &String::from_str(&env, "payload_ref"),
+            &String::from_str(&env, "testnet"),
+            &1000i128,
+            &token,
+            &None,
+            &Vec::new(&env),
+            &None,
+        );
+    }
+
+    let records = client.get_records_since(&3u64, &10u32);
+    assert_eq!(records.len(), 2);
+    assert_eq!(records.get(0).unwrap().id, 4u64);
+    assert_eq!(records.get(1).unwrap().id, 5u64);
+
+    // Limit caps the batch; nothing is returned once caught up
+    assert_eq!(client.get_records_since(&0u64, &2u32).len(), 2);
+    assert_eq!(client.get_records_since(&5u64, &10u32).len(), 0);
+
+    // Ids missing from storage are skipped
+    let mut imported = client.get_record(&5u64);
+    imported.id = 8;
+    client.import_record(&imported);
+    let records = client.get_records_since(&5u64, &10u32);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records.get(0).unwrap().id, 8u64);
+}
+
+#[test]
+fn test_stake_lock() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    env.ledger().set_timestamp(1_000);
+    let stake_id = client.log_stake(
+        &user,
+        &String::from_str(&env, "stake_hash"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &5000i128,
+        &token,
+        &2_000u64,
+    );
+
+    let record = client.get_record(&stake_id);
+    assert_eq!(record.action_type, ActionType::STAKE);
+    assert_eq!(record.lock_until, Some(2_000u64));
+    assert!(client.is_locked(&stake_id));
+
+    // Unlocked once ledger time reaches lock_until
+    env.ledger().set_timestamp(2_000);
+    assert!(!client.is_locked(&stake_id));
+
+    // Records without a lock never report locked
+    let nft_id = client.log_and_route(
+        &user,
+        &ActionType::NFT,
+        &String::from_str(&env, "hash_1"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &1000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+    assert!(!client.is_locked(&nft_id));
+    assert_eq!(client.try_is_locked(&99u64), Err(Ok(RegistryError::RecordNotFound)));
+
+    // A lock must end in the future
+    let stake = |lock_until: u64| client.try_log_stake(
+        &user,
+        &String::from_str(&env, "stake_hash"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &5000i128,
+        &token,
+        &lock_until,
+    );
+    assert_eq!(stake(2_000), Err(Ok(RegistryError::InvalidLockTime)));
+    assert_eq!(stake(1_999), Err(Ok(RegistryError::InvalidLockTime)));
+    assert!(stake(2_001).is_ok());
+}
+
+#[test]
+fn test_stake_full_event_carries_lock() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.set_emit_full_event(&true);
+
+    env.ledger().set_timestamp(1_000);
+    let id = client.log_stake(
+        &user,
+        &String::from_str(&env, "stake_hash"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &5000i128,
+        &token,
+        &2_000u64,
+    );
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("sw_test"), Symbol::new(&env, "action_full")).into_val(&env)
+    );
+    let record: ActionRecord = data.into_val(&env);
+    assert_eq!(record.lock_until, Some(2_000u64));
+    assert_eq!(record, client.get_record(&id));
+}
+
+#[test]
+fn test_volume_tier_fee_discount() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = create_funded_token(&env, &user, 100_000i128);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.set_fee_bps(&500u32); // 5%
+
+    // 2% from 10_000 routed, 1% from 50_000
+    let tiers = Vec::from_array(&env, [
+        VolumeTier { min_volume: 10_000, fee_bps: 200 },
+        VolumeTier { min_volume: 50_000, fee_bps: 100 },
+    ]);
+    client.set_volume_tiers(&tiers);
+    assert_eq!(client.get_volume_tiers(), tiers);
+
+    let log = |plan_hash: &str| client.log_and_route(
+        &user,
+        &ActionType::DEFI,
+        &String::from_str(&env, plan_hash),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &10_000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+
+    // The first action pays the full rate and crosses the first threshold
+    let first = log("hash_1");
+    assert_eq!(client.get_record(&first).fee_amount, 500i128);
+    assert_eq!(client.get_user_fee_bps(&user), 200u32);
+
+    // The next one is charged at the tier rate
+    let second = log("hash_2");
+    assert_eq!(client.get_record(&second).fee_amount, 200i128);
+
+    // Tiers must ascend and stay within the fee cap
+    let unordered = Vec::from_array(&env, [
+        VolumeTier { min_volume: 50_000, fee_bps: 100 },
+        VolumeTier { min_volume: 10_000, fee_bps: 200 },
+    ]);
+    assert_eq!(client.try_set_volume_tiers(&unordered), Err(Ok(RegistryError::InvalidAmount)));
+    let too_high = Vec::from_array(&env, [VolumeTier { min_volume: 10_000, fee_bps: 1500 }]);
+    assert_eq!(client.try_set_volume_tiers(&too_high), Err(Ok(RegistryError::InvalidFeeRate)));
+}
+
+#[test]
+fn test_volume_tiers_follow_config_cooldown() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.set_config_change_cooldown(&3600);
+
+    let tiers = Vec::from_array(&env, [VolumeTier { min_volume: 10_000, fee_bps: 200 }]);
+    assert_eq!(client.try_set_volume_tiers(&tiers), Err(Ok(RegistryError::ConfigChangeCooldown)));
+    assert_eq!(client.get_volume_tiers().len(), 0);
+
+    env.ledger().set_timestamp(3600);
+    client.set_volume_tiers(&tiers);
+    assert_eq!(client.get_volume_tiers(), tiers);
+
+    let history = client.get_config_history();
+    assert_eq!(history.get(history.len() - 1).unwrap().timestamp, 3600);
+}
+
+#[test]
+fn test_full_action_event() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let counterparty = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    assert!(!client.get_config().emit_full_event);
+    client.set_emit_full_event(&true);
+
+    let id = client.log_and_route(
+        &user,
+        &ActionType::DEFI,
+        &String::from_str(&env, "hash_1"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "mainnet"),
+        &2500i128,
+        &token,
+        &None,
+        &Vec::from_array(&env, [String::from_str(&env, "swap")]),
+        &Some(counterparty.clone()),
+    );
+
+    // A single event carries the whole record in place of the compact action event
+    let events = env.events().all();
+    assert_eq!(events.len(), 1);
+    let (emitter, topics, data) = events.last().unwrap();
+    assert_eq!(emitter, contract_id);
+    assert_eq!(
+        topics,
+        (symbol_short!("sw_test"), Symbol::new(&env, "action_full")).into_val(&env)
+    );
+    let record: ActionRecord = data.into_val(&env);
+    assert_eq!(record.id, id);
+    assert_eq!(record.user, user);
+    assert_eq!(record.action_type, ActionType::DEFI);
+    assert_eq!(record.plan_hash, String::from_str(&env, "hash_1"));
+    assert_eq!(record.payload_ref, String::from_str(&env, "payload_ref"));
+    assert_eq!(record.network, String::from_str(&env, "mainnet"));
+    assert_eq!(record.fee_amount, 0i128);
+    assert_eq!(record.total_amount, 2500i128);
+    assert_eq!(record.counterparty, Some(counterparty));
+    assert_eq!(record, client.get_record(&id));
+}
+
+#[test]
+fn test_fees_in_range() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = create_funded_token(&env, &user, 100_000i128);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+    client.set_fee_bps(&500u32); // 5%
+
+    // Fees of 50, 100 and 150 at t=100, 200 and 300
+    for (timestamp, amount, plan_hash) in [(100u64, 1_000i128, "hash_1"), (200, 2_000, "hash_2"), (300, 3_000, "hash_3")] {
+        env.ledger().set_timestamp(timestamp);
+        client.log_and_route(
+            &user,
+            &ActionType::NFT,
+            &String::from_str(&env, plan_hash),
+            &String::from_str(&env, "payload_ref"),
+            &String::from_str(&env, "testnet"),
+            &amount,
+            &token,
+            &None,
+            &Vec::new(&env),
+            &None,
+        );
+    }
+
+    assert_eq!(client.fees_in_range(&0u64, &1_000u64), 300i128);
+    // Bounds are inclusive
+    assert_eq!(client.fees_in_range(&200u64, &300u64), 250i128);
+    assert_eq!(client.fees_in_range(&150u64, &250u64), 100i128);
+    assert_eq!(client.fees_in_range(&301u64, &400u64), 0i128);
+}
+
+#[test]
+fn test_append_tx_ref_length_limit() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let owner = Address::generate(&env);
+    let fee_wallet = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let contract_id = create_registry_contract(&env, &owner, &fee_wallet);
+    let client = StellarWizardRegistryClient::new(&env, &contract_id);
+
+    let action_id = client.log_and_route(
+        &user,
+        &ActionType::DEFI,
+        &String::from_str(&env, "test_hash"),
+        &String::from_str(&env, "payload_ref"),
+        &String::from_str(&env, "testnet"),
+        &5000i128,
+        &token,
+        &None,
+        &Vec::new(&env),
+        &None,
+    );
+
+    let at_limit = String::from_bytes(&env, &[b'a'; MAX_TX_REF_LEN as usize]);
+    client.append_tx_ref(&user, &action_id, &at_limit);
+    assert_eq!(client.get_record(&action_id).tx_refs.len(), 1);
+
+    let over_limit = String::from_bytes(&env, &[b'a'; MAX_TX_REF_LEN as usize + 1]);
+    assert_eq!(
+        client.try_append_tx_ref(&user, &action_id, &over_limit),
+        Err(Ok(RegistryError::TxRefTooLong))
+    );
+    assert_eq!(client.get_record(&action_id).tx_refs.len(), 1);
+}
@@ -66,7 +66,9 @@ if [ $? -eq 0 ] && [ ! -z "$REGISTRY_CONTRACT_ID" ]; then
         -- \
         initialize \
         --owner "$DEPLOYER_PUBLIC" \
-        --nft_contract "$NFT_CONTRACT_ID"
+        --fee_bps 0 \
+        --fee_wallet "$DEPLOYER_PUBLIC" \
+        --event_prefix testnet
         
     if [ $? -eq 0 ]; then
         echo "✅ Registry contract initialized successfully!"
@@ -75,7 +75,9 @@ export async function POST(request: NextRequest) {
 
     // ===== 1) BUILD (sin firmar) =====
     // Firma del ctor en tu contrato:
-    //   (caller: Address, name: String, symbol: String, uri_base: String, royalties_bps: u32)
+    //   (caller: Address, name: String, symbol: String, uri_base: String, royalties_bps: u32,
+    //    salt: Option<BytesN<32>>, category: Option<String>, min_mint_amount: Option<u32>,
+    //    royalties_enforced: bool, initial_minter: Option<Address>)
     const unsignedXdr = await stellarService.buildCreateCollectionTransaction(
       {
         caller: userAddress,
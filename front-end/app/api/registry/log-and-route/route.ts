@@ -17,6 +17,8 @@ interface LogAndRouteRequest {
   network: 'TESTNET' | 'MAINNET';
   totalAmount: string; // Amount in stroops
   tokenAddress?: string; // Optional, defaults to native XLM
+  tags?: string[]; // Optional labels for filtering, e.g. "swap:xlm-usdc"
+  counterparty?: string; // Optional second party to the action
 }
 
 export async function POST(request: NextRequest) {
@@ -28,6 +30,8 @@ export async function POST(request: NextRequest) {
       payloadRef,
       network,
       totalAmount,
+      tags,
+      counterparty,
       tokenAddress
     }: LogAndRouteRequest = await request.json();
 
@@ -76,6 +80,13 @@ export async function POST(request: NextRequest) {
       nativeToScVal(network, { type: 'string' }),                  // network
       nativeToScVal(BigInt(totalAmount), { type: 'i128' }),        // total_amount
       Address.fromString(tokenAddr).toScVal(),                     // token_address
+      xdr.ScVal.scvVoid(),                                         // fee_recipient_override
+      xdr.ScVal.scvVec(
+        (tags || []).map(tag => nativeToScVal(tag, { type: 'string' }))
+      ),                                                           // tags
+      counterparty
+        ? Address.fromString(counterparty).toScVal()
+        : xdr.ScVal.scvVoid(),                                     // counterparty
     ];
 
     // Build the operation
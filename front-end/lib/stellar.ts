@@ -51,6 +51,10 @@ export interface CreateCollectionParams {
   symbol: string;
   uri_base: string;
   royalties_bps: number; // Royalties in basis points (0-10000)
+  category?: string; // Optional marketplace category (art, gaming, music...)
+  min_mint_amount?: number; // Minimum NFTs per mint (defaults to 1)
+  royalties_enforced?: boolean; // Enforce royalties on secondary sales (defaults to advisory)
+  initial_minter?: string; // Address granted the minter role on the new collection
 }
 
 export interface MintNFTParams {
@@ -129,6 +133,18 @@ export class StellarService {
       const symbolScVal = nativeToScVal(params.symbol, { type: 'string' });
       const uriBaseScVal = nativeToScVal(params.uri_base, { type: 'string' });
       const royaltiesBpsScVal = nativeToScVal(params.royalties_bps, { type: 'u32' });
+      // No custom salt: the factory derives one from the collection id
+      const saltScVal = xdr.ScVal.scvVoid();
+      const categoryScVal = params.category
+        ? nativeToScVal(params.category, { type: 'string' })
+        : xdr.ScVal.scvVoid();
+      const minMintAmountScVal = params.min_mint_amount
+        ? nativeToScVal(params.min_mint_amount, { type: 'u32' })
+        : xdr.ScVal.scvVoid();
+      const royaltiesEnforcedScVal = nativeToScVal(params.royalties_enforced ?? false, { type: 'bool' });
+      const initialMinterScVal = params.initial_minter
+        ? new Address(params.initial_minter).toScVal()
+        : xdr.ScVal.scvVoid();
 
       console.log('StellarService: ScVal conversion completed');
 
@@ -139,7 +155,12 @@ export class StellarService {
         nameScVal,
         symbolScVal,
         uriBaseScVal,
-        royaltiesBpsScVal
+        royaltiesBpsScVal,
+        saltScVal,
+        categoryScVal,
+        minMintAmountScVal,
+        royaltiesEnforcedScVal,
+        initialMinterScVal
       );
 
       // Build transaction